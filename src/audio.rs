@@ -0,0 +1,411 @@
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use rodio::source::UniformSourceIterator;
+use rodio::{Decoder, OutputStream, Sink, Source};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Wav,
+    Mp3,
+    /// Opus in an Ogg container
+    #[value(alias = "ogg")]
+    Opus,
+    Flac,
+    /// Headerless signed 16-bit little-endian PCM
+    Pcm,
+}
+
+impl AudioFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus => "opus",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Pcm => "pcm",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Opus => "audio/ogg",
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Pcm => "audio/L16",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "wav" | "wave" => Some(AudioFormat::Wav),
+            "mp3" => Some(AudioFormat::Mp3),
+            "ogg" | "oga" | "opus" => Some(AudioFormat::Opus),
+            "flac" => Some(AudioFormat::Flac),
+            "pcm" | "raw" => Some(AudioFormat::Pcm),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_extension(path.extension()?.to_str()?)
+    }
+
+    /// Identifies encoded audio by its magic bytes. Raw PCM has none and is
+    /// never detected.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        match data {
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(AudioFormat::Wav),
+            [b'I', b'D', b'3', ..] => Some(AudioFormat::Mp3),
+            [0xFF, second, ..] if second & 0xE0 == 0xE0 => Some(AudioFormat::Mp3),
+            [b'f', b'L', b'a', b'C', ..] => Some(AudioFormat::Flac),
+            [b'O', b'g', b'g', b'S', ..] => Some(AudioFormat::Opus),
+            _ => None,
+        }
+    }
+}
+
+/// Requested shape of the audio written to disk.
+#[derive(Debug, Clone, Copy)]
+pub struct OutputOptions {
+    pub format: AudioFormat,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+}
+
+/// PCM samples (interleaved when stereo) decoded from a provider's output.
+pub struct DecodedAudio {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<i16>,
+}
+
+impl DecodedAudio {
+    pub fn duration(&self) -> Duration {
+        let frames = self.samples.len() as f64 / self.channels.max(1) as f64;
+        Duration::from_secs_f64(frames / self.sample_rate.max(1) as f64)
+    }
+}
+
+/// Decodes WAV, MP3, FLAC or Ogg Vorbis bytes into PCM.
+pub fn decode(data: &[u8]) -> Result<DecodedAudio> {
+    let decoder = decoder(data)?;
+    let sample_rate = decoder.sample_rate();
+    let channels = decoder.channels();
+    Ok(DecodedAudio {
+        sample_rate,
+        channels,
+        samples: decoder.collect(),
+    })
+}
+
+/// Decodes audio and resamples it to the given channel count and sample rate.
+pub fn decode_as(data: &[u8], channels: u16, sample_rate: u32) -> Result<Vec<i16>> {
+    let decoder = decoder(data)?;
+    Ok(UniformSourceIterator::<_, i16>::new(decoder, channels, sample_rate).collect())
+}
+
+pub fn encode_wav(audio: &DecodedAudio) -> Result<Vec<u8>> {
+    let spec = hound::WavSpec {
+        channels: audio.channels,
+        sample_rate: audio.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut buffer = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut buffer, spec)?;
+    for &sample in &audio.samples {
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+
+    Ok(buffer.into_inner())
+}
+
+/// Transcodes provider output into the requested format.
+///
+/// WAV and raw PCM are encoded in-process; MP3, Opus and FLAC are handed to
+/// `ffmpeg`. Audio already in the requested shape is returned as is, apart
+/// from fixing up the sizes in a streamed WAV header.
+pub async fn convert(data: &[u8], options: &OutputOptions) -> Result<Vec<u8>> {
+    let resample = options.sample_rate.is_some() || options.channels.is_some();
+    if !resample && AudioFormat::detect(data) == Some(options.format) {
+        let mut data = data.to_vec();
+        repair_wav_header(&mut data);
+        return Ok(data);
+    }
+
+    let mut audio = decode(data)?;
+    if resample {
+        let channels = options.channels.unwrap_or(audio.channels);
+        let sample_rate = options.sample_rate.unwrap_or(audio.sample_rate);
+        audio = DecodedAudio {
+            samples: decode_as(data, channels, sample_rate)?,
+            sample_rate,
+            channels,
+        };
+    }
+
+    match options.format {
+        AudioFormat::Wav => encode_wav(&audio),
+        AudioFormat::Pcm => Ok(audio.samples.iter().flat_map(|s| s.to_le_bytes()).collect()),
+        AudioFormat::Mp3 => run_ffmpeg(&encode_wav(&audio)?, &["-f", "mp3"]).await,
+        AudioFormat::Opus => run_ffmpeg(&encode_wav(&audio)?, &["-c:a", "libopus", "-f", "ogg"]).await,
+        AudioFormat::Flac => run_ffmpeg(&encode_wav(&audio)?, &["-f", "flac"]).await,
+    }
+}
+
+/// Scales the audio by a gain in decibels, returning WAV.
+pub fn apply_gain(data: &[u8], gain_db: f32) -> Result<Vec<u8>> {
+    let mut audio = decode(data)?;
+    let factor = 10f32.powf(gain_db / 20.0);
+    for sample in &mut audio.samples {
+        *sample = (*sample as f32 * factor).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    }
+    encode_wav(&audio)
+}
+
+/// Changes speed (`rate` as a multiplier) and pitch (in semitones)
+/// independently of each other, returning WAV. Needs `ffmpeg`.
+pub async fn adjust_tempo_and_pitch(data: &[u8], rate: f32, semitones: f32) -> Result<Vec<u8>> {
+    let audio = decode(data)?;
+    let pitch_factor = 2f32.powf(semitones / 12.0);
+
+    // Raising the sample rate shifts pitch and speed together; atempo then
+    // undoes the speed change and applies the requested rate instead.
+    let mut filters = Vec::new();
+    if pitch_factor != 1.0 {
+        let shifted_rate = (audio.sample_rate as f32 * pitch_factor).round() as u32;
+        filters.push(format!("asetrate={}", shifted_rate));
+        filters.push(format!("aresample={}", audio.sample_rate));
+    }
+
+    // atempo only accepts factors between 0.5 and 2.0, so chain it
+    let mut tempo = rate / pitch_factor;
+    while tempo > 2.0 {
+        filters.push("atempo=2.0".to_string());
+        tempo /= 2.0;
+    }
+    while tempo < 0.5 {
+        filters.push("atempo=0.5".to_string());
+        tempo /= 0.5;
+    }
+    if tempo != 1.0 {
+        filters.push(format!("atempo={}", tempo));
+    }
+
+    if filters.is_empty() {
+        return Ok(data.to_vec());
+    }
+
+    let filter = filters.join(",");
+    run_ffmpeg(&encode_wav(&audio)?, &["-af", &filter, "-f", "wav"]).await
+}
+
+async fn run_ffmpeg(wav: &[u8], output_args: &[&str]) -> Result<Vec<u8>> {
+    let mut child = tokio::process::Command::new("ffmpeg")
+        .kill_on_drop(true)
+        .args(["-hide_banner", "-loglevel", "error", "-f", "wav", "-i", "pipe:0"])
+        .args(output_args)
+        .arg("pipe:1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("ffmpeg is required for MP3, Opus and FLAC output and for rate/pitch changes on this provider. Please install ffmpeg")?;
+
+    // Feed stdin while reading stdout so a full pipe cannot deadlock us;
+    // stdin is closed once everything is written
+    let mut stdin = child.stdin.take().ok_or_else(|| anyhow!("Failed to open ffmpeg stdin"))?;
+    let writer = async move { stdin.write_all(wav).await };
+
+    let (written, output) = tokio::join!(writer, child.wait_with_output());
+    let output = output?;
+    written.context("Failed to send audio to ffmpeg")?;
+
+    if !output.status.success() {
+        return Err(anyhow!("ffmpeg failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(output.stdout)
+}
+
+/// Playing time of a clip, measured by decoding it.
+pub fn duration(data: &[u8]) -> Result<Duration> {
+    Ok(decode(data)?.duration())
+}
+
+/// Plays audio on the default output device, blocking until it finishes or
+/// `max_duration` has been played.
+pub fn play(data: &[u8], max_duration: Option<Duration>) -> Result<()> {
+    let (_stream, handle) = OutputStream::try_default()
+        .map_err(|e| anyhow!("No audio output device available: {}", e))?;
+    let sink = Sink::try_new(&handle).map_err(|e| anyhow!("Failed to open audio output: {}", e))?;
+
+    let source = decoder(data)?;
+    match max_duration {
+        Some(max) => sink.append(source.take_duration(max)),
+        None => sink.append(source),
+    }
+    sink.sleep_until_end();
+
+    Ok(())
+}
+
+/// A piece of the final audio: synthesized bytes or a pause.
+pub enum AudioSegment<'a> {
+    Clip(&'a [u8]),
+    Silence(Duration),
+}
+
+/// Joins separately synthesized clips into a single WAV file.
+pub fn concatenate(parts: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let segments: Vec<AudioSegment> = parts.iter().map(|part| AudioSegment::Clip(part)).collect();
+    join(&segments)
+}
+
+/// Joins clips and pauses into a single WAV file.
+///
+/// Every clip is converted to the sample rate and channel count of the first
+/// one, so clips that came from different providers still line up.
+pub fn join(segments: &[AudioSegment]) -> Result<Vec<u8>> {
+    let first = segments
+        .iter()
+        .find_map(|segment| match segment {
+            AudioSegment::Clip(data) => Some(*data),
+            AudioSegment::Silence(_) => None,
+        })
+        .ok_or_else(|| anyhow!("No audio to concatenate"))?;
+
+    let reference = decoder(first)?;
+    let mut combined = DecodedAudio {
+        sample_rate: reference.sample_rate(),
+        channels: reference.channels(),
+        samples: Vec::new(),
+    };
+
+    for segment in segments {
+        match segment {
+            AudioSegment::Clip(data) => {
+                let samples = decode_as(data, combined.channels, combined.sample_rate)?;
+                combined.samples.extend(samples);
+            }
+            AudioSegment::Silence(duration) => {
+                let frames = (duration.as_secs_f64() * combined.sample_rate as f64).round() as usize;
+                combined
+                    .samples
+                    .extend(std::iter::repeat_n(0, frames * combined.channels as usize));
+            }
+        }
+    }
+
+    encode_wav(&combined)
+}
+
+fn decoder(data: &[u8]) -> Result<Decoder<Cursor<Vec<u8>>>> {
+    let mut data = data.to_vec();
+    repair_wav_header(&mut data);
+    Decoder::new(Cursor::new(data)).map_err(|e| anyhow!("Unsupported audio data: {}", e))
+}
+
+/// Engines that write WAV to a pipe (espeak `--stdout`) cannot seek back to
+/// fill in the chunk sizes and leave placeholders instead. Clamp the RIFF and
+/// `data` sizes to what is actually present so decoders stop at the real end.
+fn repair_wav_header(data: &mut [u8]) {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return;
+    }
+
+    let riff_size = (data.len() - 8) as u32;
+    data[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let declared = u32::from_le_bytes([
+            data[offset + 4],
+            data[offset + 5],
+            data[offset + 6],
+            data[offset + 7],
+        ]) as usize;
+        let available = data.len() - offset - 8;
+
+        if &data[offset..offset + 4] == b"data" {
+            let size = declared.min(available) as u32;
+            data[offset + 4..offset + 8].copy_from_slice(&size.to_le_bytes());
+            return;
+        }
+
+        offset += 8 + declared + declared % 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A WAV header as written to a pipe, with placeholder sizes, followed by
+    /// a LIST chunk and four bytes of samples.
+    fn streamed_wav() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"RIFF");
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(b"WAVEfmt ");
+        data.extend_from_slice(&16u32.to_le_bytes());
+        data.extend_from_slice(&[1, 0, 1, 0]);
+        data.extend_from_slice(&22050u32.to_le_bytes());
+        data.extend_from_slice(&44100u32.to_le_bytes());
+        data.extend_from_slice(&[2, 0, 16, 0]);
+        data.extend_from_slice(b"LIST");
+        data.extend_from_slice(&4u32.to_le_bytes());
+        data.extend_from_slice(b"INFO");
+        data.extend_from_slice(b"data");
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(&[1, 0, 2, 0]);
+        data
+    }
+
+    fn size_at(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn repair_wav_header_clamps_placeholder_sizes() {
+        let mut data = streamed_wav();
+        repair_wav_header(&mut data);
+
+        assert_eq!(size_at(&data, 4) as usize, data.len() - 8);
+        let data_chunk = data.len() - 12;
+        assert_eq!(&data[data_chunk..data_chunk + 4], b"data");
+        assert_eq!(size_at(&data, data_chunk + 4), 4);
+
+        assert_eq!(decode(&streamed_wav()).unwrap().samples, [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn convert_repairs_wav_passed_through() {
+        let options = OutputOptions {
+            format: AudioFormat::Wav,
+            sample_rate: None,
+            channels: None,
+        };
+        let mut repaired = streamed_wav();
+        repair_wav_header(&mut repaired);
+        assert_eq!(convert(&streamed_wav(), &options).await.unwrap(), repaired);
+    }
+
+    #[test]
+    fn repair_wav_header_ignores_other_data() {
+        let mut data = b"ID3\x04 not a wav file".to_vec();
+        let original = data.clone();
+        repair_wav_header(&mut data);
+        assert_eq!(data, original);
+    }
+}
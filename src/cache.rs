@@ -1,61 +1,365 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::fs;
 
-pub fn generate_cache_key(text: &str, provider: &str, language: &str, voice: Option<&str>) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(text.as_bytes());
-    hasher.update(provider.as_bytes());
-    hasher.update(language.as_bytes());
-    
-    if let Some(v) = voice {
-        hasher.update(v.as_bytes());
+use crate::audio::{self, AudioFormat};
+use crate::providers::{SynthesisRequest, Voice};
+
+/// How long a provider's voice list is reused before it is queried again.
+const VOICE_LIST_TTL: Duration = Duration::from_secs(7 * 86_400);
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+const LOCK_FILE: &str = ".lock";
+
+#[derive(Serialize, Deserialize)]
+struct VoiceList {
+    fetched_at: u64,
+    voices: Vec<Voice>,
+}
+
+/// Limits enforced whenever an entry is written and by `cache prune`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CachePolicy {
+    pub max_size_bytes: Option<u64>,
+    pub max_age: Option<Duration>,
+}
+
+/// Outcome of a prune pass.
+#[derive(Debug, Default)]
+pub struct PruneReport {
+    pub removed_entries: u32,
+    pub freed_bytes: u64,
+    pub remaining_entries: u32,
+    pub remaining_bytes: u64,
+}
+
+/// Sidecar record stored as `<key>.json` next to each `<key>.audio` entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheMetadata {
+    pub format: Option<AudioFormat>,
+    pub mime_type: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    pub duration_secs: Option<f64>,
+    pub provider: String,
+    pub language: String,
+    pub voice: Option<String>,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+}
+
+impl CacheMetadata {
+    /// Describes freshly synthesized audio, probing it for format details.
+    pub fn describe(audio_data: &[u8], provider: &str, language: &str, voice: Option<&str>) -> Self {
+        let format = AudioFormat::detect(audio_data);
+        let decoded = audio::decode(audio_data).ok();
+
+        CacheMetadata {
+            format,
+            mime_type: format.map(|f| f.mime_type().to_string()),
+            sample_rate: decoded.as_ref().map(|a| a.sample_rate),
+            channels: decoded.as_ref().map(|a| a.channels),
+            duration_secs: decoded.as_ref().map(|a| a.duration().as_secs_f64()),
+            provider: provider.to_string(),
+            language: language.to_string(),
+            voice: voice.map(str::to_string),
+            created_at: unix_now(),
+        }
     }
-    
-    let result = hasher.finalize();
-    hex::encode(result)
+}
+
+/// Bump whenever the key layout changes. Entries under an older layout are
+/// never looked up again and age out through pruning.
+const CACHE_KEY_VERSION: u32 = 2;
+
+/// Hashes every input that affects the synthesized audio: the full request,
+/// the provider, and the provider's engine version.
+pub fn generate_cache_key(request: &SynthesisRequest, provider: &str, engine_version: Option<&str>) -> String {
+    // Serializing to JSON gives each field its own delimited slot, so
+    // ("ab", "c") and ("a", "bc") no longer hash the same.
+    let key = serde_json::json!({
+        "version": CACHE_KEY_VERSION,
+        "provider": provider,
+        "engine_version": engine_version,
+        "request": request,
+    });
+
+    hex::encode(Sha256::digest(key.to_string().as_bytes()))
 }
 
 pub async fn get_cached_audio(cache_key: &str) -> Result<Option<Vec<u8>>> {
     let cache_path = get_cache_path(cache_key);
-    
-    if cache_path.exists() {
-        let data = fs::read(&cache_path).await?;
-        Ok(Some(data))
-    } else {
-        Ok(None)
+
+    // Another process may evict the entry at any time, so a missing file is
+    // a cache miss rather than an error
+    let Some(data) = read_if_exists(&cache_path).await? else {
+        return Ok(None);
+    };
+    // The modification time doubles as the last-used time for LRU eviction
+    let _ = std::fs::File::options()
+        .write(true)
+        .open(&cache_path)
+        .and_then(|f| f.set_modified(SystemTime::now()));
+    Ok(Some(data))
+}
+
+pub async fn get_cache_metadata(cache_key: &str) -> Result<Option<CacheMetadata>> {
+    let content = read_if_exists(&get_metadata_path(cache_key)).await?;
+    Ok(content.and_then(|content| serde_json::from_slice(&content).ok()))
+}
+
+pub async fn cache_audio(
+    cache_key: &str,
+    audio_data: &[u8],
+    metadata: &CacheMetadata,
+    policy: &CachePolicy,
+) -> Result<()> {
+    let _lock = lock_cache_dir().await?;
+
+    // The sidecar goes first so an entry is never visible without it
+    write_atomic(&get_metadata_path(cache_key), serde_json::to_string_pretty(metadata)?.as_bytes())?;
+    write_atomic(&get_cache_path(cache_key), audio_data)?;
+
+    evict(policy, Some(cache_key), false).await?;
+
+    Ok(())
+}
+
+/// Voice list saved by `cache_voices`, unless it is missing or stale.
+pub async fn get_cached_voices(provider: &str) -> Result<Option<Vec<Voice>>> {
+    let path = get_voices_path(provider);
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path).await?;
+    let Ok(list) = serde_json::from_str::<VoiceList>(&content) else {
+        return Ok(None);
+    };
+
+    if unix_now().saturating_sub(list.fetched_at) > VOICE_LIST_TTL.as_secs() {
+        return Ok(None);
+    }
+
+    Ok(Some(list.voices))
+}
+
+pub async fn cache_voices(provider: &str, voices: &[Voice]) -> Result<()> {
+    let list = VoiceList {
+        fetched_at: unix_now(),
+        voices: voices.to_vec(),
+    };
+    write_atomic(&get_voices_path(provider), serde_json::to_string_pretty(&list)?.as_bytes())
+}
+
+/// An OAuth2 access token saved between runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedToken {
+    pub access_token: String,
+    /// Unix time at which the token stops being accepted.
+    pub expires_at: u64,
+}
+
+impl CachedToken {
+    /// Tokens are refreshed a minute early so a request started just before
+    /// expiry is not rejected in flight.
+    pub fn is_fresh(&self) -> bool {
+        self.expires_at > unix_now() + TOKEN_EXPIRY_MARGIN.as_secs()
+    }
+}
+
+/// Token saved by `cache_token` under `name`, unless it is missing or about
+/// to expire.
+pub async fn get_cached_token(name: &str) -> Result<Option<CachedToken>> {
+    let path = get_token_path(name);
+
+    if !path.exists() {
+        return Ok(None);
     }
+
+    let content = fs::read_to_string(&path).await?;
+    Ok(serde_json::from_str::<CachedToken>(&content)
+        .ok()
+        .filter(CachedToken::is_fresh))
+}
+
+pub async fn cache_token(name: &str, token: &CachedToken) -> Result<()> {
+    // Temporary files are created readable only by the user, which
+    // `write_atomic` keeps for the token once it is renamed into place
+    write_atomic(&get_token_path(name), serde_json::to_string(token)?.as_bytes())
+}
+
+pub async fn clear_text_cache(request: &SynthesisRequest, provider: &str, engine_version: Option<&str>) -> Result<()> {
+    let _lock = lock_cache_dir().await?;
+    remove_entry(&generate_cache_key(request, provider, engine_version)).await
+}
+
+/// Removes expired entries, then least recently used ones until the cache
+/// fits in the size limit. With `dry_run` nothing is deleted.
+pub async fn prune(policy: &CachePolicy, dry_run: bool) -> Result<PruneReport> {
+    let _lock = lock_cache_dir().await?;
+    evict(policy, None, dry_run).await
 }
 
-pub async fn cache_audio(cache_key: &str, audio_data: &[u8]) -> Result<()> {
+struct CacheEntry {
+    key: String,
+    size: u64,
+    created_at: u64,
+    last_used: SystemTime,
+}
+
+async fn evict(policy: &CachePolicy, keep: Option<&str>, dry_run: bool) -> Result<PruneReport> {
+    let mut report = PruneReport::default();
+    let mut entries = list_entries().await?;
+
+    let now = unix_now();
+    let (expired, mut live): (Vec<_>, Vec<_>) = entries.drain(..).partition(|entry| {
+        Some(entry.key.as_str()) != keep
+            && policy
+                .max_age
+                .is_some_and(|max_age| now.saturating_sub(entry.created_at) > max_age.as_secs())
+    });
+
+    let mut doomed = expired;
+    if let Some(max_size) = policy.max_size_bytes {
+        live.sort_by_key(|entry| entry.last_used);
+        let mut total: u64 = live.iter().map(|entry| entry.size).sum();
+        let mut kept = Vec::new();
+        for entry in live {
+            if total > max_size && Some(entry.key.as_str()) != keep {
+                total -= entry.size;
+                doomed.push(entry);
+            } else {
+                kept.push(entry);
+            }
+        }
+        live = kept;
+    }
+
+    for entry in &doomed {
+        if !dry_run {
+            remove_entry(&entry.key).await?;
+        }
+        report.removed_entries += 1;
+        report.freed_bytes += entry.size;
+    }
+    report.remaining_entries = live.len() as u32;
+    report.remaining_bytes = live.iter().map(|entry| entry.size).sum();
+
+    Ok(report)
+}
+
+async fn list_entries() -> Result<Vec<CacheEntry>> {
     let cache_dir = get_cache_dir();
-    fs::create_dir_all(&cache_dir).await?;
-    
-    let cache_path = get_cache_path(cache_key);
-    fs::write(&cache_path, audio_data).await?;
-    
+    let mut result = Vec::new();
+
+    if !cache_dir.exists() {
+        return Ok(result);
+    }
+
+    let mut entries = fs::read_dir(&cache_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("audio") {
+            continue;
+        }
+        let Some(key) = path.file_stem().and_then(|s| s.to_str()).map(str::to_string) else {
+            continue;
+        };
+        let Ok(file_metadata) = entry.metadata().await else {
+            continue;
+        };
+
+        let last_used = file_metadata.modified().unwrap_or(UNIX_EPOCH);
+        let sidecar = get_cache_metadata(&key).await?;
+        let sidecar_size = fs::metadata(get_metadata_path(&key)).await.map_or(0, |m| m.len());
+        let created_at = match &sidecar {
+            Some(info) => info.created_at,
+            None => last_used.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+        };
+
+        result.push(CacheEntry {
+            key,
+            size: file_metadata.len() + sidecar_size,
+            created_at,
+            last_used,
+        });
+    }
+
+    Ok(result)
+}
+
+async fn remove_entry(cache_key: &str) -> Result<()> {
+    for path in [get_cache_path(cache_key), get_metadata_path(cache_key)] {
+        match fs::remove_file(&path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+
     Ok(())
 }
 
-pub async fn clear_text_cache(text: &str, provider: &str, language: &str, voice: Option<&str>) -> Result<()> {
-    let cache_key = generate_cache_key(text, provider, language, voice);
-    let cache_path = get_cache_path(&cache_key);
-    
-    if cache_path.exists() {
-        fs::remove_file(&cache_path).await?;
+async fn read_if_exists(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path).await {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
     }
-    
+}
+
+/// Writes `data` to a temporary file next to `path` and renames it into
+/// place, so readers in other processes never see a partial file.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    std::fs::create_dir_all(dir)?;
+
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(data)?;
+    file.persist(path)?;
+
     Ok(())
 }
 
-pub async fn clear_all_cache() -> Result<()> {
+/// Exclusive lock on the cache directory, held while entries are written
+/// or evicted so concurrent processes do not delete each other's
+/// files mid-write. Released when the returned file is dropped.
+async fn lock_cache_dir() -> Result<std::fs::File> {
     let cache_dir = get_cache_dir();
-    
-    if cache_dir.exists() {
-        fs::remove_dir_all(&cache_dir).await?;
+    fs::create_dir_all(&cache_dir).await?;
+
+    let file = std::fs::File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(cache_dir.join(LOCK_FILE))?;
+    let file = tokio::task::spawn_blocking(move || file.lock().map(|_| file)).await??;
+
+    Ok(file)
+}
+
+/// Removes every entry, voice list and token. The lock file stays: deleting
+/// it while another process holds it would let the next one lock a new file.
+pub async fn clear_all_cache() -> Result<()> {
+    let _lock = lock_cache_dir().await?;
+
+    let mut entries = fs::read_dir(get_cache_dir()).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_name() == LOCK_FILE {
+            continue;
+        }
+        if entry.file_type().await?.is_dir() {
+            fs::remove_dir_all(entry.path()).await?;
+        } else {
+            fs::remove_file(entry.path()).await?;
+        }
     }
-    
+
     Ok(())
 }
 
@@ -68,14 +372,40 @@ pub async fn show_cache_stats() -> Result<()> {
     }
     
     let mut total_size = 0u64;
-    let mut file_count = 0u32;
+    let mut entry_count = 0u32;
+    let mut total_duration = 0f64;
+    let mut by_provider: BTreeMap<String, u32> = BTreeMap::new();
+    let mut by_format: BTreeMap<String, u32> = BTreeMap::new();
+    let mut oldest: Option<u64> = None;
     
     let mut entries = fs::read_dir(&cache_dir).await?;
     while let Some(entry) = entries.next_entry().await? {
-        if let Ok(metadata) = entry.metadata().await {
-            if metadata.is_file() {
-                total_size += metadata.len();
-                file_count += 1;
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        total_size += metadata.len();
+
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("audio") {
+            continue;
+        }
+        entry_count += 1;
+
+        let cache_key = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        match get_cache_metadata(cache_key).await? {
+            Some(info) => {
+                total_duration += info.duration_secs.unwrap_or(0.0);
+                *by_provider.entry(info.provider).or_default() += 1;
+                let format = info.format.map_or("unknown", |f| f.extension());
+                *by_format.entry(format.to_string()).or_default() += 1;
+                oldest = Some(oldest.map_or(info.created_at, |o| o.min(info.created_at)));
+            }
+            None => {
+                *by_provider.entry("unknown".to_string()).or_default() += 1;
+                *by_format.entry("unknown".to_string()).or_default() += 1;
             }
         }
     }
@@ -83,8 +413,23 @@ pub async fn show_cache_stats() -> Result<()> {
     let size_mb = total_size as f64 / (1024.0 * 1024.0);
     
     println!("Cache Statistics:");
-    println!("  Files: {}", file_count);
+    println!("  Entries: {}", entry_count);
     println!("  Total size: {:.2} MB", size_mb);
+    println!("  Total audio: {:.1} seconds", total_duration);
+    if let Some(created_at) = oldest {
+        let age_days = unix_now().saturating_sub(created_at) / 86_400;
+        println!("  Oldest entry: {} days old", age_days);
+    }
+    if !by_provider.is_empty() {
+        println!("  By provider:");
+        for (provider, count) in &by_provider {
+            println!("    {}: {}", provider, count);
+        }
+        println!("  By format:");
+        for (format, count) in &by_format {
+            println!("    {}: {}", format, count);
+        }
+    }
     println!("  Cache directory: {}", cache_dir.display());
     
     Ok(())
@@ -92,10 +437,90 @@ pub async fn show_cache_stats() -> Result<()> {
 
 fn get_cache_dir() -> PathBuf {
     dirs::cache_dir()
-        .unwrap_or_else(|| std::env::temp_dir())
+        .unwrap_or_else(std::env::temp_dir)
         .join("tts-cli")
 }
 
 fn get_cache_path(cache_key: &str) -> PathBuf {
     get_cache_dir().join(format!("{}.audio", cache_key))
-}
\ No newline at end of file
+}
+
+fn get_voices_path(provider: &str) -> PathBuf {
+    get_cache_dir().join("voices").join(format!("{}.json", provider))
+}
+
+fn get_token_path(name: &str) -> PathBuf {
+    get_cache_dir().join("tokens").join(format!("{}.json", name))
+}
+
+fn get_metadata_path(cache_key: &str) -> PathBuf {
+    get_cache_dir().join(format!("{}.json", cache_key))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(text: &str) -> SynthesisRequest {
+        SynthesisRequest {
+            text: text.to_string(),
+            language: "en".to_string(),
+            voice: None,
+            ssml: false,
+            rate: 1.0,
+            pitch: 0.0,
+            volume: 0.0,
+        }
+    }
+
+    /// Writes an entry without a sidecar, so its age comes from `last_used`.
+    fn write_entry(key: &str, size: usize, last_used: SystemTime) {
+        let path = get_cache_path(key);
+        std::fs::write(&path, vec![0u8; size]).unwrap();
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(last_used).unwrap();
+    }
+
+    // The only test that touches the cache directory, which it points at a
+    // temporary one
+    #[tokio::test]
+    async fn evict_removes_expired_then_least_recently_used_entries() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::env::set_var("XDG_CACHE_HOME", temp_dir.path());
+        std::fs::create_dir_all(get_cache_dir()).unwrap();
+
+        let now = SystemTime::now();
+        let minutes_ago = |minutes: u64| now - Duration::from_secs(minutes * 60);
+        write_entry("expired", 10, now - Duration::from_secs(10 * 86_400));
+        write_entry("oldest", 100, minutes_ago(3));
+        write_entry("older", 100, minutes_ago(2));
+        write_entry("newest", 100, minutes_ago(1));
+
+        let policy = CachePolicy {
+            max_size_bytes: Some(250),
+            max_age: Some(Duration::from_secs(7 * 86_400)),
+        };
+
+        let report = evict(&policy, Some("oldest"), true).await.unwrap();
+        assert_eq!((report.removed_entries, report.freed_bytes), (2, 110));
+        assert!(get_cache_path("expired").exists(), "a dry run removes nothing");
+
+        let report = evict(&policy, Some("oldest"), false).await.unwrap();
+        assert_eq!((report.removed_entries, report.remaining_entries, report.remaining_bytes), (2, 2, 200));
+        for (key, kept) in [("expired", false), ("oldest", true), ("older", false), ("newest", true)] {
+            assert_eq!(get_cache_path(key).exists(), kept, "{}", key);
+        }
+    }
+
+    #[test]
+    fn cache_keys_keep_fields_apart() {
+        let ab_c = generate_cache_key(&request("ab"), "c", None);
+        let a_bc = generate_cache_key(&request("a"), "bc", None);
+        assert_ne!(ab_c, a_bc);
+    }
+}
@@ -1,24 +1,156 @@
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 use tokio::fs;
 
+use crate::audio::AudioFormat;
+use crate::cache::CachePolicy;
+
+const DEFAULT_PROVIDER_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_MAX_RETRIES: u32 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub default_provider: String,
     pub default_language: String,
     pub default_voice: Option<String>,
+    /// Speaking rate multiplier (1.0 = normal)
+    pub default_rate: f32,
+    /// Pitch shift in semitones
+    pub default_pitch: f32,
+    /// Volume gain in dB
+    pub default_volume: f32,
     pub cache_enabled: bool,
+    /// Evict least recently used entries once the cache grows past this size.
+    pub cache_max_size_mb: Option<u64>,
+    /// Evict entries older than this, regardless of use.
+    pub cache_max_age_days: Option<u64>,
+    /// Extra time allowed on top of a clip's length before playback is
+    /// considered stuck.
+    pub playback_grace_secs: u64,
+    /// Upper bound on how long a single playback may run.
+    pub max_playback_secs: Option<u64>,
+    /// Providers tried, in order, when the requested one fails. Defaults to
+    /// every built-in provider; an empty list disables fallback.
+    pub fallback_order: Option<Vec<String>>,
     pub providers: HashMap<String, ProviderConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProviderConfig {
     pub enabled: bool,
     pub api_key: Option<String>,
+    /// Base URL of the provider's API, e.g. a regional endpoint or a proxy.
+    /// Overridden by `TTS_CLI_<PROVIDER>_ENDPOINT` in the environment.
     pub endpoint: Option<String>,
     pub voice_mapping: HashMap<String, String>,
+    /// Voice model: an `.onnx` file or a directory of them for piper, the
+    /// model name (e.g. `tts-1`) for openai.
+    pub model: Option<String>,
+    /// Give up on a single synthesis call after this many seconds.
+    pub timeout_secs: Option<u64>,
+    /// Retries after a transient failure such as HTTP 429 or 5xx.
+    pub max_retries: u32,
+    /// Makes this entry a provider of its own that runs an external command.
+    pub command: Option<CommandConfig>,
+}
+
+/// An external TTS engine run as a command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandConfig {
+    /// Program and arguments. `{text}`, `{voice}`, `{lang}` and `{output}`
+    /// are replaced with the text, the voice (empty when none is set), the
+    /// BCP-47 language and the path of the file to write audio to.
+    pub argv: Vec<String>,
+    /// How the text reaches the command.
+    #[serde(default)]
+    pub input: CommandInput,
+    /// Where the command writes its audio.
+    #[serde(default)]
+    pub output: CommandOutput,
+    /// Format of the audio the command writes.
+    #[serde(default = "default_command_format", deserialize_with = "deserialize_command_format")]
+    pub format: AudioFormat,
+    /// Languages the engine speaks; any language when empty.
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Longest text the engine accepts; longer input is split into chunks.
+    #[serde(default)]
+    pub max_input_bytes: Option<usize>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandInput {
+    /// Only through `{text}` in `argv`
+    #[default]
+    Argv,
+    /// Written to the command's standard input
+    Stdin,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandOutput {
+    /// The file named by `{output}`
+    #[default]
+    File,
+    /// The command's standard output
+    Stdout,
+}
+
+fn default_command_format() -> AudioFormat {
+    AudioFormat::Wav
+}
+
+/// Only formats that can be decoded for playback and joining are accepted.
+/// Headerless PCM carries no sample rate or channel count, and Opus has no
+/// decoder here.
+fn deserialize_command_format<'de, D: Deserializer<'de>>(deserializer: D) -> Result<AudioFormat, D::Error> {
+    let format = AudioFormat::deserialize(deserializer)?;
+    if matches!(format, AudioFormat::Pcm | AudioFormat::Opus) {
+        return Err(serde::de::Error::custom(format!(
+            "command providers cannot output {}; use wav, mp3 or flac",
+            format.extension()
+        )));
+    }
+    Ok(format)
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        ProviderConfig {
+            enabled: true,
+            api_key: None,
+            endpoint: None,
+            voice_mapping: HashMap::new(),
+            model: None,
+            timeout_secs: None,
+            max_retries: DEFAULT_MAX_RETRIES,
+            command: None,
+        }
+    }
+}
+
+impl ProviderConfig {
+    /// Endpoint for `provider`: the `TTS_CLI_<PROVIDER>_ENDPOINT` environment
+    /// variable, then `endpoint`, then `default`. A trailing slash is dropped.
+    pub fn endpoint_or(&self, provider: &str, default: &str) -> String {
+        let variable = format!("TTS_CLI_{}_ENDPOINT", provider.to_ascii_uppercase().replace('-', "_"));
+        std::env::var(variable)
+            .ok()
+            .filter(|endpoint| !endpoint.is_empty())
+            .or_else(|| self.endpoint.clone())
+            .unwrap_or_else(|| default.to_string())
+            .trim_end_matches('/')
+            .to_string()
+    }
 }
 
 impl Default for Config {
@@ -26,43 +158,36 @@ impl Default for Config {
         let mut providers = HashMap::new();
         
         providers.insert("gcloud".to_string(), ProviderConfig {
-            enabled: true,
-            api_key: None,
-            endpoint: None,
             voice_mapping: HashMap::from([
                 ("en-US".to_string(), "en-US-Wavenet-D".to_string()),
                 ("es-ES".to_string(), "es-ES-Wavenet-C".to_string()),
                 ("fr-FR".to_string(), "fr-FR-Wavenet-D".to_string()),
                 ("de-DE".to_string(), "de-DE-Wavenet-D".to_string()),
             ]),
+            ..ProviderConfig::default()
         });
         
-        providers.insert("espeak".to_string(), ProviderConfig {
-            enabled: true,
-            api_key: None,
-            endpoint: None,
-            voice_mapping: HashMap::new(),
-        });
-        
-        providers.insert("festival".to_string(), ProviderConfig {
-            enabled: true,
-            api_key: None,
-            endpoint: None,
-            voice_mapping: HashMap::new(),
-        });
-        
-        providers.insert("say".to_string(), ProviderConfig {
-            enabled: true,
-            api_key: None,
-            endpoint: None,
-            voice_mapping: HashMap::new(),
-        });
+        providers.insert("espeak".to_string(), ProviderConfig::default());
+        providers.insert("festival".to_string(), ProviderConfig::default());
+        providers.insert("say".to_string(), ProviderConfig::default());
+        providers.insert("pico2wave".to_string(), ProviderConfig::default());
+        providers.insert("flite".to_string(), ProviderConfig::default());
+        providers.insert("piper".to_string(), ProviderConfig::default());
+        providers.insert("openai".to_string(), ProviderConfig::default());
         
         Config {
             default_provider: "gcloud".to_string(),
             default_language: "en-US".to_string(),
             default_voice: None,
+            default_rate: 1.0,
+            default_pitch: 0.0,
+            default_volume: 0.0,
             cache_enabled: true,
+            cache_max_size_mb: Some(500),
+            cache_max_age_days: None,
+            playback_grace_secs: 5,
+            max_playback_secs: None,
+            fallback_order: None,
             providers,
         }
     }
@@ -95,6 +220,13 @@ impl Config {
         Ok(())
     }
     
+    pub fn cache_policy(&self) -> CachePolicy {
+        CachePolicy {
+            max_size_bytes: self.cache_max_size_mb.map(|mb| mb.saturating_mul(1024 * 1024)),
+            max_age: self.cache_max_age_days.map(|days| Duration::from_secs(days.saturating_mul(86_400))),
+        }
+    }
+
     pub fn get_provider_config(&self, provider: &str) -> Option<&ProviderConfig> {
         self.providers.get(provider)
     }
@@ -106,6 +238,35 @@ impl Config {
             .get(language)
             .cloned()
     }
+
+    pub fn provider_timeout(&self, provider: &str) -> Duration {
+        self.get_provider_config(provider)
+            .and_then(|p| p.timeout_secs)
+            .map_or(DEFAULT_PROVIDER_TIMEOUT, Duration::from_secs)
+    }
+
+    pub fn provider_max_retries(&self, provider: &str) -> u32 {
+        self.get_provider_config(provider)
+            .map_or(DEFAULT_MAX_RETRIES, |p| p.max_retries)
+    }
+
+    /// Providers without a config entry are treated as enabled.
+    pub fn is_provider_enabled(&self, provider: &str) -> bool {
+        self.get_provider_config(provider).is_none_or(|p| p.enabled)
+    }
+
+    /// Default voice when none was given on the command line: the provider's
+    /// `voice_mapping` entry for the language, then `default_voice` for the
+    /// default provider.
+    pub fn resolve_voice(&self, provider: &str, language: &str) -> Option<String> {
+        self.get_voice_for_language(provider, language).or_else(|| {
+            if provider == self.default_provider {
+                self.default_voice.clone()
+            } else {
+                None
+            }
+        })
+    }
 }
 
 fn get_config_path() -> PathBuf {
@@ -0,0 +1,161 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+/// A BCP-47 language tag reduced to the parts that matter for picking a
+/// voice: `zh-Hant-TW` is language `zh`, script `Hant`, region `TW`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageTag {
+    pub language: String,
+    pub script: Option<String>,
+    pub region: Option<String>,
+    pub variants: Vec<String>,
+}
+
+impl LanguageTag {
+    /// Parses a tag such as `en`, `en-US`, `en_us` or `sr-Latn-RS`.
+    /// Extensions and private-use subtags are accepted but dropped.
+    pub fn parse(tag: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid language tag '{}'. Expected a BCP-47 tag such as en-US", tag);
+
+        let mut subtags = tag.trim().split(['-', '_']).peekable();
+
+        let language = subtags
+            .next()
+            .filter(|s| (2..=3).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphabetic()))
+            .ok_or_else(invalid)?
+            .to_ascii_lowercase();
+
+        let script = subtags
+            .next_if(|s| s.len() == 4 && s.chars().all(|c| c.is_ascii_alphabetic()))
+            .map(|s| {
+                let lower = s.to_ascii_lowercase();
+                lower[..1].to_ascii_uppercase() + &lower[1..]
+            });
+
+        let region = subtags
+            .next_if(|s| {
+                (s.len() == 2 && s.chars().all(|c| c.is_ascii_alphabetic()))
+                    || (s.len() == 3 && s.chars().all(|c| c.is_ascii_digit()))
+            })
+            .map(|s| s.to_ascii_uppercase());
+
+        let mut variants = Vec::new();
+        for subtag in subtags {
+            // Singletons start extensions (`u-...`) or private use (`x-...`)
+            if subtag.len() == 1 {
+                break;
+            }
+            let is_variant = subtag.chars().all(|c| c.is_ascii_alphanumeric())
+                && ((5..=8).contains(&subtag.len())
+                    || (subtag.len() == 4 && subtag.starts_with(|c: char| c.is_ascii_digit())));
+            if !is_variant {
+                return Err(invalid());
+            }
+            variants.push(subtag.to_ascii_lowercase());
+        }
+
+        Ok(LanguageTag {
+            language,
+            script,
+            region,
+            variants,
+        })
+    }
+}
+
+impl fmt::Display for LanguageTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.language)?;
+        if let Some(script) = &self.script {
+            write!(f, "-{}", script)?;
+        }
+        if let Some(region) = &self.region {
+            write!(f, "-{}", region)?;
+        }
+        for variant in &self.variants {
+            write!(f, "-{}", variant)?;
+        }
+        Ok(())
+    }
+}
+
+/// Picks the entry of a provider's language table that best serves `wanted`,
+/// returning it in the provider's own spelling.
+///
+/// Candidates must share the primary language. An exact match wins, then one
+/// with the same region, then a generic entry (`en` for `en-AU`), then the
+/// first regional entry (`en-US` for a plain `en`).
+pub fn best_match(wanted: &LanguageTag, supported: &[String]) -> Option<String> {
+    let candidates: Vec<(&String, LanguageTag)> = supported
+        .iter()
+        .filter_map(|s| Some((s, LanguageTag::parse(s).ok()?)))
+        .filter(|(_, tag)| tag.language == wanted.language)
+        .filter(|(_, tag)| wanted.script.is_none() || tag.script.is_none() || tag.script == wanted.script)
+        .collect();
+
+    let rank = |tag: &LanguageTag| -> u8 {
+        if tag.region == wanted.region && tag.script == wanted.script {
+            0
+        } else if tag.region.is_some() && tag.region == wanted.region {
+            1
+        } else if tag.region.is_none() {
+            2
+        } else {
+            3
+        }
+    };
+
+    candidates
+        .iter()
+        .min_by_key(|(_, tag)| rank(tag))
+        .map(|(original, _)| (*original).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supported(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
+
+    fn best(wanted: &str, tags: &[&str]) -> Option<String> {
+        best_match(&LanguageTag::parse(wanted).unwrap(), &supported(tags))
+    }
+
+    #[test]
+    fn parse_normalizes_case_and_separators() {
+        let tag = LanguageTag::parse("ZH_hant_tw").unwrap();
+        assert_eq!(tag.language, "zh");
+        assert_eq!(tag.script.as_deref(), Some("Hant"));
+        assert_eq!(tag.region.as_deref(), Some("TW"));
+        assert_eq!(tag.to_string(), "zh-Hant-TW");
+
+        assert_eq!(LanguageTag::parse("es-419").unwrap().region.as_deref(), Some("419"));
+        assert_eq!(LanguageTag::parse("sl-rozaj-biske").unwrap().variants, ["rozaj", "biske"]);
+        assert_eq!(LanguageTag::parse("en-US-u-ca-gregory").unwrap().to_string(), "en-US");
+    }
+
+    #[test]
+    fn parse_rejects_malformed_tags() {
+        for tag in ["", "e", "english", "en-U$", "12-US", "en-US-ab"] {
+            assert!(LanguageTag::parse(tag).is_err(), "{} should be rejected", tag);
+        }
+    }
+
+    #[test]
+    fn best_match_prefers_exact_then_region_then_generic() {
+        let tags = ["en", "en-US", "en-GB", "pt-PT", "pt-BR", "sr-Cyrl", "sr-Latn"];
+        assert_eq!(best("en-GB", &tags).as_deref(), Some("en-GB"));
+        assert_eq!(best("en-AU", &tags).as_deref(), Some("en"));
+        assert_eq!(best("en_us", &tags).as_deref(), Some("en-US"));
+        assert_eq!(best("pt", &tags).as_deref(), Some("pt-PT"));
+        assert_eq!(best("sr-Latn-RS", &tags).as_deref(), Some("sr-Latn"));
+        assert_eq!(best("de", &tags), None);
+    }
+
+    #[test]
+    fn best_match_keeps_the_provider_spelling() {
+        assert_eq!(best("pt-BR", &["pt", "pt-br"]).as_deref(), Some("pt-br"));
+    }
+}
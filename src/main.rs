@@ -1,10 +1,23 @@
 use anyhow::{anyhow, Result};
 use clap::{Parser, Subcommand};
+use std::io::Write;
 use std::path::PathBuf;
+use std::time::Duration;
 
+mod audio;
 mod cache;
 mod providers;
 mod config;
+mod language;
+mod ssml;
+mod text;
+
+use audio::{AudioFormat, OutputOptions};
+use cache::CacheMetadata;
+use config::Config;
+use language::LanguageTag;
+use providers::{ProviderRegistry, SynthesisRequest, TtsProvider, Voice};
+use serde::Serialize;
 
 #[derive(Parser)]
 #[command(name = "tts-cli")]
@@ -24,94 +37,244 @@ struct Cli {
 enum Commands {
     /// Synthesize text to speech
     Speak {
-        /// Text to synthesize
-        text: String,
-        /// TTS provider to use
-        #[arg(short, long, default_value = "gcloud")]
-        provider: String,
+        /// Text to synthesize (reads stdin when omitted or `-`)
+        text: Option<String>,
+        /// Read the text to synthesize from a file (UTF-8, or UTF-16 with a BOM)
+        #[arg(short, long, conflicts_with = "text")]
+        file: Option<PathBuf>,
+        /// Treat the text as SSML (implied when it starts with `<speak>`)
+        #[arg(long)]
+        ssml: bool,
+        /// TTS provider to use (defaults to `default_provider` from the config)
+        #[arg(short, long)]
+        provider: Option<String>,
         /// Voice to use
         #[arg(short, long)]
         voice: Option<String>,
-        /// Language code (e.g., en-US, es-ES)
-        #[arg(short, long, default_value = "en-US")]
-        language: String,
+        /// Language code, e.g. en-US, es-ES (defaults to `default_language` from the config)
+        #[arg(short, long)]
+        language: Option<String>,
+        /// Speaking rate multiplier, 0.25 to 4.0 (defaults to `default_rate` from the config)
+        #[arg(long)]
+        rate: Option<f32>,
+        /// Pitch shift in semitones, -20 to 20 (defaults to `default_pitch` from the config)
+        #[arg(long, allow_negative_numbers = true)]
+        pitch: Option<f32>,
+        /// Volume gain in dB, -96 to 16 (defaults to `default_volume` from the config)
+        #[arg(long, allow_negative_numbers = true)]
+        volume: Option<f32>,
         /// Output file path (optional, will play audio directly if not provided)
         #[arg(short, long)]
         output: Option<PathBuf>,
         /// Save to temp file instead of playing audio directly
         #[arg(long)]
         no_play: bool,
+        /// Format of the saved audio (inferred from the --output extension when omitted)
+        #[arg(long, value_enum)]
+        format: Option<AudioFormat>,
+        /// Resample the saved audio to this rate in Hz
+        #[arg(long)]
+        sample_rate: Option<u32>,
+        /// Number of channels in the saved audio (1 = mono, 2 = stereo)
+        #[arg(long, value_parser = clap::value_parser!(u16).range(1..=2))]
+        channels: Option<u16>,
+        /// Print the text's phonemes in IPA instead of speaking it (espeak)
+        #[arg(long, conflicts_with_all = ["output", "no_play"])]
+        ipa: bool,
+        /// Only use the requested provider, even if it fails
+        #[arg(long)]
+        no_fallback: bool,
         /// Disable cache
         #[arg(long)]
         no_cache: bool,
         /// Clear cache for this text
         #[arg(long)]
         clear_cache: bool,
+        /// Stop playback after this many seconds (defaults to `max_playback_secs` from the config)
+        #[arg(long, value_name = "SECONDS")]
+        max_duration: Option<u64>,
     },
     /// List available providers
     Providers,
+    /// List the voices each provider offers
+    Voices {
+        /// Only list voices of this provider
+        #[arg(short, long)]
+        provider: Option<String>,
+        /// Only list voices speaking this language (e.g. en or en-US)
+        #[arg(short, long)]
+        language: Option<String>,
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+        /// Query the providers again instead of using the cached lists
+        #[arg(long)]
+        refresh: bool,
+    },
     /// Clear all cache
     ClearCache,
+    /// Manage the audio cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
     /// Show cache statistics
     CacheStats,
 }
 
+#[derive(Subcommand)]
+enum CacheCommands {
+    /// Remove expired and least recently used entries
+    Prune {
+        /// Show what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Size limit in MB (defaults to `cache_max_size_mb` from the config)
+        #[arg(long)]
+        max_size_mb: Option<u64>,
+        /// Age limit in days (defaults to `cache_max_age_days` from the config)
+        #[arg(long)]
+        max_age_days: Option<u64>,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let config = Config::load().await?;
+    let registry = ProviderRegistry::with_builtin(&config);
 
     match cli.command {
         Commands::Speak {
             text,
+            file,
+            ssml,
             provider,
             voice,
             language,
+            rate,
+            pitch,
+            volume,
             output,
             no_play,
+            format,
+            sample_rate,
+            channels,
+            ipa,
+            no_fallback,
             no_cache,
             clear_cache,
+            max_duration,
         } => {
+            let text = text::read_input(text, file.as_deref())?;
+            let provider = provider.unwrap_or_else(|| config.default_provider.clone());
+            let language = language.unwrap_or_else(|| config.default_language.clone());
+            let language = LanguageTag::parse(&language)?.to_string();
+            let voice = voice.or_else(|| config.resolve_voice(&provider, &language));
+            let rate = check_range("rate", rate.unwrap_or(config.default_rate), 0.25, 4.0)?;
+            let pitch = check_range("pitch", pitch.unwrap_or(config.default_pitch), -20.0, 20.0)?;
+            let volume = check_range("volume", volume.unwrap_or(config.default_volume), -96.0, 16.0)?;
+
+            let ssml = ssml || ssml::is_ssml(&text);
+
+            if ipa {
+                let engine = registry
+                    .get(&provider)
+                    .ok_or_else(|| anyhow!("Unknown provider: {}", provider))?;
+                let request = SynthesisRequest {
+                    text,
+                    language,
+                    voice,
+                    ssml,
+                    rate,
+                    pitch,
+                    volume,
+                };
+                println!("{}", providers::phonemes(engine, &request).await?);
+                return Ok(());
+            }
+
+            // Chunks must fit whichever provider ends up synthesizing them, so
+            // the smallest limit in the chain applies. Splitting at sentence
+            // boundaries would cut through SSML elements, so markup is always
+            // sent whole.
+            let max_bytes = provider_chain(&registry, &config, &provider, !no_fallback)
+                .iter()
+                .filter(|name| config.is_provider_enabled(name))
+                .filter_map(|name| registry.get(name).and_then(|p| p.max_input_bytes()))
+                .min();
+            let chunks = match max_bytes {
+                Some(max_bytes) if !ssml => text::split_into_chunks(&text, max_bytes),
+                _ => vec![text.clone()],
+            };
+            let requests: Vec<SynthesisRequest> = chunks
+                .into_iter()
+                .map(|chunk| SynthesisRequest {
+                    text: chunk,
+                    language: language.clone(),
+                    voice: voice.clone(),
+                    ssml,
+                    rate,
+                    pitch,
+                    volume,
+                })
+                .collect();
+
             if clear_cache {
-                cache::clear_text_cache(&text, &provider, &language, voice.as_deref()).await?;
+                let engine_version = registry.get(&provider).and_then(|p| p.engine_version());
+                for request in &requests {
+                    cache::clear_text_cache(request, &provider, engine_version.as_deref()).await?;
+                }
                 println!("Cache cleared for the specified text");
                 return Ok(());
             }
 
-            let audio_data = if no_cache {
-                synthesize_with_fallback(&text, &provider, &language, voice.as_deref()).await?
-            } else {
-                let cache_key = cache::generate_cache_key(&text, &provider, &language, voice.as_deref());
-                
-                if let Some(cached_data) = cache::get_cached_audio(&cache_key).await? {
-                    println!("Using cached audio");
-                    cached_data
-                } else {
-                    let audio_data = synthesize_with_fallback(&text, &provider, &language, voice.as_deref()).await?;
-                    cache::cache_audio(&cache_key, &audio_data).await?;
-                    println!("Audio cached for future use");
-                    audio_data
+            let use_cache = !no_cache && config.cache_enabled;
+            let mut parts = Vec::with_capacity(requests.len());
+            for (index, request) in requests.iter().enumerate() {
+                if requests.len() > 1 {
+                    println!("Synthesizing chunk {}/{}", index + 1, requests.len());
                 }
+                parts.push(synthesize_cached(&registry, &config, request, &provider, !no_fallback, use_cache).await?);
+            }
+
+            let audio_data = if parts.len() == 1 {
+                parts.remove(0)
+            } else {
+                audio::concatenate(&parts)?
+            };
+
+            // Fall back to whatever the provider produced when neither --format
+            // nor a recognised --output extension says otherwise
+            let output_format = format
+                .or_else(|| output.as_deref().and_then(AudioFormat::from_path))
+                .or_else(|| AudioFormat::detect(&audio_data))
+                .unwrap_or(AudioFormat::Wav);
+            let output_options = OutputOptions {
+                format: output_format,
+                sample_rate,
+                channels,
             };
 
             if let Some(output_path) = output {
-                std::fs::write(&output_path, audio_data)?;
+                std::fs::write(&output_path, audio::convert(&audio_data, &output_options).await?)?;
                 println!("Audio saved to: {}", output_path.display());
             } else if no_play {
                 // User explicitly requested to save to file instead of playing
-                let temp_file = std::env::temp_dir().join("tts_output.wav");
-                std::fs::write(&temp_file, &audio_data)?;
+                let temp_file = save_to_temp_file(&audio::convert(&audio_data, &output_options).await?, output_format)?;
                 println!("Audio saved to: {}", temp_file.display());
                 println!("You can play it with: aplay {} or mpv {}", temp_file.display(), temp_file.display());
             } else {
                 // Default behavior: play audio directly
-                match try_play_audio_with_timeout(&audio_data) {
+                let grace = Duration::from_secs(config.playback_grace_secs);
+                let max_duration = max_duration.or(config.max_playback_secs).map(Duration::from_secs);
+                match try_play_audio_with_timeout(&audio_data, grace, max_duration) {
                     Ok(_) => {
                         println!("Audio playback completed");
                     }
                     Err(e) => {
                         println!("Audio playback failed: {}", e);
-                        let temp_file = std::env::temp_dir().join("tts_output.wav");
-                        std::fs::write(&temp_file, &audio_data)?;
+                        let temp_file = save_to_temp_file(&audio::convert(&audio_data, &output_options).await?, output_format)?;
                         println!("Audio saved to: {}", temp_file.display());
                         println!("You can play it with: aplay {} or mpv {}", temp_file.display(), temp_file.display());
                         println!("Use --no-play flag to save to file by default");
@@ -120,12 +283,90 @@ async fn main() -> Result<()> {
             }
         }
         Commands::Providers => {
-            providers::list_providers();
+            registry.list_providers();
+        }
+        Commands::Voices {
+            provider,
+            language,
+            json,
+            refresh,
+        } => {
+            let selected: Vec<_> = match &provider {
+                Some(name) => vec![registry
+                    .get(name)
+                    .ok_or_else(|| anyhow!("Unknown provider: {}", name))?],
+                None => registry
+                    .iter()
+                    .filter(|p| config.is_provider_enabled(p.name()) && p.is_available())
+                    .collect(),
+            };
+
+            let language = language.as_deref().map(LanguageTag::parse).transpose()?;
+            let mut listing = Vec::new();
+            for provider in selected {
+                let wanted = language.as_ref().map(|tag| providers::voice_languages(provider, tag));
+                let voices = match list_voices_cached(provider, refresh).await {
+                    Ok(voices) => voices,
+                    Err(e) => {
+                        eprintln!("Warning: could not list {} voices: {}", provider.name(), e);
+                        continue;
+                    }
+                };
+                for voice in voices {
+                    if wanted.as_ref().is_none_or(|tags| tags.iter().any(|tag| voice.speaks(tag))) {
+                        listing.push(ProviderVoice {
+                            provider: provider.name().to_string(),
+                            voice,
+                        });
+                    }
+                }
+            }
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&listing)?);
+            } else if listing.is_empty() {
+                println!("No voices found");
+            } else {
+                for entry in &listing {
+                    println!(
+                        "  {:<10} {:<32} {:<24} {}",
+                        entry.provider,
+                        entry.voice.name,
+                        entry.voice.languages.join(", "),
+                        entry.voice.gender.as_deref().unwrap_or("")
+                    );
+                }
+            }
         }
         Commands::ClearCache => {
             cache::clear_all_cache().await?;
             println!("All cache cleared");
         }
+        Commands::Cache {
+            command: CacheCommands::Prune {
+                dry_run,
+                max_size_mb,
+                max_age_days,
+            },
+        } => {
+            let mut config = config;
+            config.cache_max_size_mb = max_size_mb.or(config.cache_max_size_mb);
+            config.cache_max_age_days = max_age_days.or(config.cache_max_age_days);
+
+            let report = cache::prune(&config.cache_policy(), dry_run).await?;
+            let verb = if dry_run { "Would remove" } else { "Removed" };
+            println!(
+                "{} {} entries ({:.2} MB)",
+                verb,
+                report.removed_entries,
+                report.freed_bytes as f64 / (1024.0 * 1024.0)
+            );
+            println!(
+                "Remaining: {} entries ({:.2} MB)",
+                report.remaining_entries,
+                report.remaining_bytes as f64 / (1024.0 * 1024.0)
+            );
+        }
         Commands::CacheStats => {
             cache::show_cache_stats().await?;
         }
@@ -134,113 +375,280 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn synthesize_with_fallback(
-    text: &str,
-    preferred_provider: &str,
-    language: &str,
-    voice: Option<&str>,
-) -> Result<Vec<u8>> {
-    // Try the preferred provider first
-    match providers::synthesize_text(text, preferred_provider, language, voice).await {
-        Ok(audio_data) => {
-            println!("Using {} provider", preferred_provider);
-            return Ok(audio_data);
+#[derive(Serialize)]
+struct ProviderVoice {
+    provider: String,
+    #[serde(flatten)]
+    voice: Voice,
+}
+
+async fn list_voices_cached(provider: &dyn TtsProvider, refresh: bool) -> Result<Vec<Voice>> {
+    if !refresh {
+        if let Some(voices) = cache::get_cached_voices(provider.name()).await? {
+            return Ok(voices);
         }
-        Err(e) => {
-            println!("Warning: {} provider failed: {}", preferred_provider, e);
+    }
+
+    let voices = provider.list_voices().await?;
+    cache::cache_voices(provider.name(), &voices).await?;
+    Ok(voices)
+}
+
+/// Saves audio to a new file in the temp directory and keeps it there for the
+/// user to play. Each run gets its own file so concurrent runs do not
+/// overwrite each other.
+fn save_to_temp_file(audio_data: &[u8], format: AudioFormat) -> Result<PathBuf> {
+    let mut file = tempfile::Builder::new()
+        .prefix("tts_output_")
+        .suffix(&format!(".{}", format.extension()))
+        .tempfile()?;
+    file.write_all(audio_data)?;
+    let (_, path) = file.keep()?;
+    Ok(path)
+}
+
+fn check_range(name: &str, value: f32, min: f32, max: f32) -> Result<f32> {
+    if (min..=max).contains(&value) {
+        Ok(value)
+    } else {
+        Err(anyhow!("{} must be between {} and {}, got {}", name, min, max, value))
+    }
+}
+
+async fn synthesize_cached(
+    registry: &ProviderRegistry,
+    config: &Config,
+    request: &SynthesisRequest,
+    provider: &str,
+    fallback: bool,
+    use_cache: bool,
+) -> Result<Vec<u8>> {
+    if !use_cache {
+        let synthesized = synthesize_with_fallback(registry, config, request, provider, fallback).await?;
+        return Ok(synthesized.audio_data);
+    }
+
+    let engine_version = registry.get(provider).and_then(|p| p.engine_version());
+    let cache_key = cache::generate_cache_key(request, provider, engine_version.as_deref());
+
+    // Earlier versions cached fallback audio under the requested provider's
+    // key; such entries are ignored and replaced once it succeeds
+    let produced_by_other = cache::get_cache_metadata(&cache_key)
+        .await?
+        .is_some_and(|metadata| metadata.provider != provider);
+    if !produced_by_other {
+        if let Some(cached_data) = cache::get_cached_audio(&cache_key).await? {
+            println!("Using cached audio");
+            return Ok(cached_data);
         }
     }
 
-    // Get available providers and try them in order
-    let available_providers = providers::get_available_providers();
-    let fallback_order = ["espeak", "festival", "say", "gcloud"];
-    
-    for provider_name in &fallback_order {
-        if provider_name == &preferred_provider {
-            continue; // Already tried
+    let synthesized = synthesize_with_fallback(registry, config, request, provider, fallback).await?;
+
+    // Audio from a fallback provider is cached under that provider and the
+    // voice it used, never under the requested provider's key, so the next
+    // call tries the requested provider again.
+    let cache_key = if synthesized.provider == provider {
+        cache_key
+    } else {
+        let produced = SynthesisRequest {
+            voice: synthesized.voice.clone(),
+            ..request.clone()
+        };
+        let engine_version = registry.get(&synthesized.provider).and_then(|p| p.engine_version());
+        cache::generate_cache_key(&produced, &synthesized.provider, engine_version.as_deref())
+    };
+    let metadata = CacheMetadata::describe(
+        &synthesized.audio_data,
+        &synthesized.provider,
+        &request.language,
+        synthesized.voice.as_deref(),
+    );
+    cache::cache_audio(&cache_key, &synthesized.audio_data, &metadata, &config.cache_policy()).await?;
+    println!("Audio cached for future use");
+    Ok(synthesized.audio_data)
+}
+
+/// Audio together with the provider and voice that actually produced it,
+/// which differ from the request when a fallback provider was used.
+struct Synthesized {
+    provider: String,
+    voice: Option<String>,
+    audio_data: Vec<u8>,
+}
+
+/// Tries `preferred_provider`, then unless `fallback` is false the providers
+/// of the configured fallback order that are available and speak the
+/// language. Every provider gets its own timeout and retries transient
+/// failures with exponential backoff.
+/// Providers to try in order: the preferred one, then the configured
+/// `fallback_order` or every registered provider.
+fn provider_chain(registry: &ProviderRegistry, config: &Config, preferred_provider: &str, fallback: bool) -> Vec<String> {
+    let mut chain = vec![preferred_provider.to_string()];
+    if fallback {
+        let order: Vec<String> = match &config.fallback_order {
+            Some(order) => order.clone(),
+            None => registry.iter().map(|p| p.name().to_string()).collect(),
+        };
+        for name in order {
+            if !chain.contains(&name) {
+                chain.push(name);
+            }
         }
-        
-        if let Some(provider) = available_providers.iter().find(|p| p.name == *provider_name && p.available) {
-            println!("Trying fallback provider: {}", provider.name);
-            match providers::synthesize_text(text, &provider.name, language, voice).await {
-                Ok(audio_data) => {
-                    println!("Successfully used {} provider", provider.name);
-                    return Ok(audio_data);
+    }
+    chain
+}
+
+async fn synthesize_with_fallback(
+    registry: &ProviderRegistry,
+    config: &Config,
+    request: &SynthesisRequest,
+    preferred_provider: &str,
+    fallback: bool,
+) -> Result<Synthesized> {
+    let language = LanguageTag::parse(&request.language)?;
+    let chain = provider_chain(registry, config, preferred_provider, fallback);
+
+    // One line per provider in the chain, reported if they all fail
+    let mut attempts = Vec::new();
+    for (position, name) in chain.iter().enumerate() {
+        let is_fallback = position > 0;
+
+        let skipped = match registry.get(name) {
+            None => Err("unknown provider".to_string()),
+            Some(_) if !config.is_provider_enabled(name) => Err("disabled in the config".to_string()),
+            Some(provider) if !provider.is_available() => Err("not available on this system".to_string()),
+            Some(provider) if provider.resolve_language(&language).is_none() => {
+                Err(format!("does not support language {}", language))
+            }
+            Some(provider) => Ok(provider),
+        };
+        let provider = match skipped {
+            Ok(provider) => provider,
+            Err(reason) => {
+                if !is_fallback {
+                    println!("Warning: {} provider skipped: {}", name, reason);
                 }
-                Err(e) => {
-                    println!("Warning: {} provider failed: {}", provider.name, e);
+                attempts.push(format!("{}: skipped, {}", name, reason));
+                continue;
+            }
+        };
+
+        // A voice picked for the preferred provider means nothing to another
+        // engine, so fallback providers use their own configured defaults.
+        let request = if is_fallback {
+            println!("Trying fallback provider: {}", name);
+            SynthesisRequest {
+                voice: config.resolve_voice(name, &request.language),
+                ..request.clone()
+            }
+        } else {
+            request.clone()
+        };
+
+        match synthesize_with_retries(config, provider, &request).await {
+            Ok(audio_data) => {
+                if is_fallback {
+                    println!("Successfully used {} provider", name);
+                } else {
+                    println!("Using {} provider", name);
                 }
+                return Ok(Synthesized {
+                    provider: name.clone(),
+                    voice: request.voice,
+                    audio_data,
+                });
+            }
+            Err(e) => {
+                println!("Warning: {} provider failed: {}", name, e);
+                attempts.push(format!("{}: {}", name, e));
             }
         }
     }
 
-    Err(anyhow!("All TTS providers failed. Please install at least one: espeak, festival, or Google Cloud SDK"))
+    Err(anyhow!(
+        "No TTS provider could synthesize the text:\n  {}",
+        attempts.join("\n  ")
+    ))
 }
 
-fn try_play_audio_with_timeout(audio_data: &[u8]) -> Result<()> {
+/// Delay before the first retry of a transient failure, doubled after each.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+async fn synthesize_with_retries(
+    config: &Config,
+    provider: &dyn TtsProvider,
+    request: &SynthesisRequest,
+) -> Result<Vec<u8>> {
+    let timeout = config.provider_timeout(provider.name());
+    let max_retries = config.provider_max_retries(provider.name());
+
+    let mut delay = RETRY_BASE_DELAY;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        // Timing out drops the synthesis future; every subprocess it runs is
+        // spawned with kill_on_drop, so none outlives it
+        let result = match tokio::time::timeout(timeout, providers::synthesize(provider, request)).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!("timed out after {}s", timeout.as_secs_f32())),
+        };
+
+        match result {
+            Err(e) if attempt <= max_retries && providers::is_retryable(&e) => {
+                println!(
+                    "Warning: {} provider failed: {}. Retrying in {:.1}s ({}/{})",
+                    provider.name(),
+                    e,
+                    delay.as_secs_f32(),
+                    attempt,
+                    max_retries
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(e) if attempt > 1 => return Err(anyhow!("{} (after {} attempts)", e, attempt)),
+            result => return result,
+        }
+    }
+}
+
+fn try_play_audio_with_timeout(audio_data: &[u8], grace: Duration, max_duration: Option<Duration>) -> Result<()> {
     use std::sync::mpsc;
     use std::thread;
-    use std::time::Duration;
+
+    let clip_duration = audio::duration(audio_data)?;
+    let play_duration = match max_duration {
+        Some(max) if max < clip_duration => {
+            println!(
+                "Audio is {:.1}s long; playback will stop after {}s",
+                clip_duration.as_secs_f64(),
+                max.as_secs()
+            );
+            max
+        }
+        _ => clip_duration,
+    };
+    let timeout = play_duration + grace;
 
     let (tx, rx) = mpsc::channel();
     let audio_data = audio_data.to_vec();
 
     // Spawn audio playback in a separate thread
     thread::spawn(move || {
-        let result = play_audio_blocking(&audio_data);
+        let result = audio::play(&audio_data, max_duration);
         let _ = tx.send(result);
     });
 
-    // Wait for completion with timeout
-    match rx.recv_timeout(Duration::from_secs(10)) {
+    // Wait for the clip to finish, allowing some slack for device start-up
+    match rx.recv_timeout(timeout) {
         Ok(result) => result,
-        Err(mpsc::RecvTimeoutError::Timeout) => {
-            Err(anyhow!("Audio playback timed out after 10 seconds - this may indicate an issue with the audio system"))
-        }
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(anyhow!(
+            "Audio playback did not finish within {:.1} seconds - this may indicate an issue with the audio system",
+            timeout.as_secs_f64()
+        )),
         Err(mpsc::RecvTimeoutError::Disconnected) => {
             Err(anyhow!("Audio playback thread disconnected unexpectedly"))
         }
     }
 }
-
-fn play_audio_blocking(audio_data: &[u8]) -> Result<()> {
-    use std::process::Command;
-    
-    // Save audio to a temporary file
-    let temp_file = std::env::temp_dir().join("tts_playback.wav");
-    std::fs::write(&temp_file, audio_data)?;
-    
-    // Try different audio players in order of preference
-    let players = ["aplay", "paplay", "mpv", "ffplay", "play"];
-    
-    for player in &players {
-        if Command::new(player).arg("--help").output().is_ok() || 
-           Command::new("which").arg(player).output().map_or(false, |o| o.status.success()) {
-            
-            let output = Command::new(player)
-                .arg(&temp_file)
-                .output();
-                
-            // Clean up temp file
-            let _ = std::fs::remove_file(&temp_file);
-            
-            match output {
-                Ok(output) if output.status.success() => {
-                    return Ok(());
-                }
-                Ok(output) => {
-                    return Err(anyhow!("Audio player {} failed: {}", player, String::from_utf8_lossy(&output.stderr)));
-                }
-                Err(e) => {
-                    // Try next player
-                    continue;
-                }
-            }
-        }
-    }
-    
-    // Clean up temp file if we get here
-    let _ = std::fs::remove_file(&temp_file);
-    
-    Err(anyhow!("No working audio player found. Please install one of: {}", players.join(", ")))
-}
\ No newline at end of file
@@ -0,0 +1,145 @@
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+use super::{SynthesisRequest, TtsProvider};
+use crate::audio::AudioFormat;
+use crate::config::{CommandConfig, CommandInput, CommandOutput};
+use crate::language::{self, LanguageTag};
+
+/// A provider defined entirely in the config file: any engine with a command
+/// line, such as RHVoice or mimic3, described by an argv template.
+pub struct CommandProvider {
+    name: String,
+    command: CommandConfig,
+}
+
+impl CommandProvider {
+    pub fn new(name: &str, command: CommandConfig) -> Self {
+        CommandProvider {
+            name: name.to_string(),
+            command,
+        }
+    }
+}
+
+#[async_trait]
+impl TtsProvider for CommandProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        self.command.description.as_deref().unwrap_or("Command from the config")
+    }
+
+    fn is_available(&self) -> bool {
+        // Running an arbitrary program just to probe it could have side
+        // effects, so only check that it exists
+        self.command.argv.first().is_some_and(|program| find_program(program))
+    }
+
+    fn output_format(&self) -> AudioFormat {
+        self.command.format
+    }
+
+    fn supported_languages(&self) -> Vec<String> {
+        self.command.languages.clone()
+    }
+
+    fn resolve_language(&self, tag: &LanguageTag) -> Option<String> {
+        if self.command.languages.is_empty() {
+            Some(tag.to_string())
+        } else {
+            language::best_match(tag, &self.command.languages)
+        }
+    }
+
+    /// The command line decides what the audio sounds like, so editing it
+    /// invalidates cached audio.
+    fn engine_version(&self) -> Option<String> {
+        Some(self.command.argv.join(" "))
+    }
+
+    fn max_input_bytes(&self) -> Option<usize> {
+        self.command.max_input_bytes
+    }
+
+    async fn synthesize(&self, request: &SynthesisRequest) -> Result<Vec<u8>> {
+        let [program, args @ ..] = self.command.argv.as_slice() else {
+            return Err(anyhow!("Provider {} has an empty argv in the config", self.name));
+        };
+        if self.command.output == CommandOutput::File && !self.command.argv.iter().any(|arg| arg.contains("{output}")) {
+            return Err(anyhow!(
+                "Provider {} writes audio to a file but its argv has no {{output}} placeholder",
+                self.name
+            ));
+        }
+
+        let output_file = tempfile::Builder::new()
+            .prefix("tts_command_")
+            .suffix(&format!(".{}", self.command.format.extension()))
+            .tempfile()?;
+        let output_path = output_file.path().to_string_lossy();
+
+        let expand = |arg: &str| {
+            arg.replace("{voice}", request.voice.as_deref().unwrap_or(""))
+                .replace("{lang}", &request.language)
+                .replace("{output}", &output_path)
+                .replace("{text}", &request.text)
+        };
+
+        let mut child = tokio::process::Command::new(expand(program))
+            .kill_on_drop(true)
+            .args(args.iter().map(|arg| expand(arg)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to run {}: {}", program, e))?;
+
+        // Engines may stream audio to stdout while still reading stdin, so
+        // the text is written while the output is read. Stdin is closed once
+        // written, or right away for commands that take the text from argv.
+        let mut stdin = child.stdin.take().ok_or_else(|| anyhow!("Failed to open {} stdin", self.name))?;
+        let input = match self.command.input {
+            CommandInput::Stdin => request.text.as_bytes(),
+            CommandInput::Argv => &[],
+        };
+        let writer = async move { stdin.write_all(input).await };
+
+        let (written, output) = tokio::join!(writer, child.wait_with_output());
+        let output = output?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{} command failed: {}",
+                self.name,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        written.with_context(|| format!("Failed to send the text to {}", self.name))?;
+
+        let audio_data = match self.command.output {
+            CommandOutput::Stdout => output.stdout,
+            CommandOutput::File => std::fs::read(output_file.path())?,
+        };
+        if audio_data.is_empty() {
+            return Err(anyhow!("{} command produced no audio", self.name));
+        }
+
+        Ok(audio_data)
+    }
+}
+
+/// Whether `program` is a path to an existing file or found on `PATH`.
+fn find_program(program: &str) -> bool {
+    if program.contains(std::path::MAIN_SEPARATOR) {
+        return Path::new(program).is_file();
+    }
+    std::env::var_os("PATH").is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| dir.join(program).is_file())
+    })
+}
@@ -0,0 +1,398 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use super::{command_version, ProsodySupport, SynthesisRequest, TtsProvider, Voice};
+use crate::audio::{self, AudioFormat};
+use crate::language::{self, LanguageTag};
+
+/// Language voices shipped with espeak, spelled as `-v` expects them. Only
+/// used when the installed engine cannot be asked for its own list.
+const LANGUAGES: &[&str] = &[
+    "af", "ar", "bg", "bs", "ca", "cs", "cy", "da", "de", "el", "en", "en-us", "eo", "es", "et",
+    "fa", "fi", "fr", "fr-be", "hi", "hr", "hu", "hy", "id", "is", "it", "ka", "kn", "ku", "la",
+    "lv", "mk", "ml", "nl", "no", "pl", "pt", "pt-pt", "ro", "ru", "sk", "sq", "sr", "sv", "sw",
+    "ta", "tr", "vi", "zh",
+];
+
+/// eSpeak NG, or the legacy eSpeak it replaced when that is all there is.
+/// Both take the same arguments.
+pub struct EspeakProvider {
+    binary: OnceLock<&'static str>,
+    /// Language codes it answers to, each with the `-v` code that speaks it.
+    languages: OnceLock<Vec<(String, String)>>,
+}
+
+impl EspeakProvider {
+    pub fn new() -> Self {
+        EspeakProvider {
+            binary: OnceLock::new(),
+            languages: OnceLock::new(),
+        }
+    }
+
+    fn binary(&self) -> &'static str {
+        self.binary.get_or_init(|| {
+            ["espeak-ng", "espeak"]
+                .into_iter()
+                .find(|binary| Command::new(binary).arg("--version").output().is_ok())
+                .unwrap_or("espeak")
+        })
+    }
+
+    fn language_table(&self) -> &[(String, String)] {
+        self.languages.get_or_init(|| {
+            let listing = Command::new(self.binary()).arg("--voices").output();
+            let rows = listing
+                .map(|output| parse_rows(&String::from_utf8_lossy(&output.stdout)))
+                .unwrap_or_default();
+            let table = language_table(&rows);
+            if table.is_empty() {
+                return LANGUAGES.iter().map(|l| (l.to_string(), l.to_string())).collect();
+            }
+            table
+        })
+    }
+}
+
+#[async_trait]
+impl TtsProvider for EspeakProvider {
+    fn name(&self) -> &str {
+        "espeak"
+    }
+
+    fn description(&self) -> &str {
+        "eSpeak NG / eSpeak TTS engine"
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new(self.binary()).arg("--version").output().is_ok()
+    }
+
+    fn output_format(&self) -> AudioFormat {
+        AudioFormat::Wav
+    }
+
+    /// The language column of `--voices`, over a hundred languages and
+    /// dialects for eSpeak NG, plus the codes from its Other Languages column
+    /// such as `en` and `zh`, which have no voice of their own.
+    fn supported_languages(&self) -> Vec<String> {
+        self.language_table().iter().map(|(code, _)| code.clone()).collect()
+    }
+
+    fn resolve_language(&self, tag: &LanguageTag) -> Option<String> {
+        resolve_in(self.language_table(), tag)
+    }
+
+    fn supports_ssml(&self) -> bool {
+        true
+    }
+
+    fn prosody_support(&self) -> ProsodySupport {
+        ProsodySupport {
+            rate: true,
+            pitch: true,
+            volume: true,
+        }
+    }
+
+    fn engine_version(&self) -> Option<String> {
+        command_version(self.binary())
+    }
+
+    fn max_input_bytes(&self) -> Option<usize> {
+        Some(4000)
+    }
+
+    async fn list_voices(&self) -> Result<Vec<Voice>> {
+        let output = tokio::process::Command::new(self.binary())
+            .kill_on_drop(true)
+            .arg("--voices")
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(anyhow!("{} command failed: {}", self.binary(), String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(parse_voices(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    async fn phonemes(&self, request: &SynthesisRequest) -> Result<String> {
+        let voice = voice_argument(self.binary(), request.voice.as_deref(), &request.language).await?;
+
+        let mut cmd = tokio::process::Command::new(self.binary());
+        cmd.kill_on_drop(true).arg("-q").arg("--ipa").arg("-v").arg(voice);
+        if request.ssml {
+            cmd.arg("-m");
+        }
+
+        let output = cmd.arg(&request.text).output().await?;
+
+        if !output.status.success() {
+            return Err(anyhow!("{} command failed: {}", self.binary(), String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    async fn synthesize(&self, request: &SynthesisRequest) -> Result<Vec<u8>> {
+        // Already mapped to one of `supported_languages` by `resolve_language`
+        let lang_code = request.language.as_str();
+
+        // espeak defaults: 175 words per minute, pitch 50 of 0-99, amplitude 100 of 0-200
+        let speed = (175.0 * request.rate).round().clamp(80.0, 450.0);
+        let pitch = (50.0 + request.pitch * 50.0 / 12.0).round().clamp(0.0, 99.0);
+        let amplitude = (100.0 * 10f32.powf(request.volume / 20.0)).round().clamp(0.0, 200.0);
+        // The top amplitude is only about +6 dB; louder requests get the rest
+        // applied to the output
+        let extra_gain = if amplitude == 200.0 { request.volume - 20.0 * 2f32.log10() } else { 0.0 };
+
+        let voice = voice_argument(self.binary(), request.voice.as_deref(), lang_code).await?;
+
+        // Killed if the caller gives up waiting, e.g. on a timeout
+        let mut cmd = tokio::process::Command::new(self.binary());
+        cmd.kill_on_drop(true)
+            .arg("-v")
+            .arg(voice)
+            .arg("-s")
+            .arg(speed.to_string())
+            .arg("-p")
+            .arg(pitch.to_string())
+            .arg("-a")
+            .arg(amplitude.to_string())
+            .arg("--stdout");
+
+        if request.ssml {
+            // Interpret SSML markup instead of speaking the tags
+            cmd.arg("-m");
+        }
+
+        let output = cmd.arg(&request.text).output().await?;
+
+        if !output.status.success() {
+            return Err(anyhow!("{} command failed: {}", self.binary(), String::from_utf8_lossy(&output.stderr)));
+        }
+
+        if extra_gain > 0.0 {
+            return audio::apply_gain(&output.stdout, extra_gain);
+        }
+        Ok(output.stdout)
+    }
+}
+
+/// Resolves `--voice` into an espeak `-v` argument, checking that the voice
+/// and variant are installed. Accepts voice names, language codes and voice
+/// files (`english-us`, `en-us`, `mb-en1`), optionally with a variant
+/// (`en+f3`). A bare variant such as `+f3` applies to the request language.
+async fn voice_argument(binary: &str, voice: Option<&str>, lang_code: &str) -> Result<String> {
+    let Some(voice) = voice else {
+        return Ok(lang_code.to_string());
+    };
+
+    let voice = if voice.starts_with('+') {
+        format!("{}{}", lang_code, voice)
+    } else {
+        voice.to_string()
+    };
+    let (base, variant) = match voice.split_once('+') {
+        Some((base, variant)) => (base, Some(variant)),
+        None => (voice.as_str(), None),
+    };
+
+    let listing = if base.starts_with("mb-") { "--voices=mb" } else { "--voices" };
+    if !voice_rows(binary, listing).await?.iter().any(|row| row_matches(row, base)) {
+        return Err(anyhow!(
+            "Unknown espeak voice '{}'. Run 'tts-cli voices --provider espeak' to list voices",
+            base
+        ));
+    }
+
+    if let Some(variant) = variant {
+        if !voice_rows(binary, "--voices=variant").await?.iter().any(|row| row_matches(row, variant)) {
+            return Err(anyhow!("Unknown espeak voice variant '{}'", variant));
+        }
+    }
+
+    Ok(voice)
+}
+
+/// Rows of an espeak voice listing such as `--voices=variant`.
+async fn voice_rows(binary: &str, listing: &str) -> Result<Vec<Vec<String>>> {
+    let output = tokio::process::Command::new(binary)
+        .kill_on_drop(true)
+        .arg(listing)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow!("{} command failed: {}", binary, String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(parse_rows(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Columns of each row of an espeak voice listing, without the header.
+fn parse_rows(listing: &str) -> Vec<Vec<String>> {
+    listing
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().map(str::to_string).collect())
+        .collect()
+}
+
+/// Maps every language code in a `--voices` listing to the `-v` code that
+/// speaks it, sorted by code. Codes from the Other Languages column, such as
+/// `(en 2)`, go to the voice listing them with the lowest priority number,
+/// unless a voice has that code as its own language.
+fn language_table(rows: &[Vec<String>]) -> Vec<(String, String)> {
+    let mut table: Vec<(String, String)> = rows
+        .iter()
+        .filter_map(|row| row.get(1))
+        .map(|language| (language.clone(), language.clone()))
+        .collect();
+
+    let mut aliases: Vec<(String, u32, String)> = Vec::new();
+    for row in rows {
+        let Some(language) = row.get(1) else {
+            continue;
+        };
+        for (alias, priority) in other_languages(row) {
+            if table.iter().any(|(code, _)| *code == alias) {
+                continue;
+            }
+            match aliases.iter_mut().find(|(code, _, _)| *code == alias) {
+                Some(entry) if priority < entry.1 => *entry = (alias, priority, language.clone()),
+                Some(_) => {}
+                None => aliases.push((alias, priority, language.clone())),
+            }
+        }
+    }
+
+    table.extend(aliases.into_iter().map(|(alias, _, language)| (alias, language)));
+    table.sort();
+    table.dedup_by(|a, b| a.0 == b.0);
+    table
+}
+
+/// The `-v` code for the entry of `table` that best serves `tag`.
+fn resolve_in(table: &[(String, String)], tag: &LanguageTag) -> Option<String> {
+    let codes: Vec<String> = table.iter().map(|(code, _)| code.clone()).collect();
+    let code = language::best_match(tag, &codes)?;
+    table.iter().find(|(listed, _)| *listed == code).map(|(_, voice)| voice.clone())
+}
+
+/// The Other Languages column of a `--voices` row, `(en-gb 3)(en 5)`, as
+/// codes with their priorities.
+fn other_languages(row: &[String]) -> Vec<(String, u32)> {
+    row.get(5..)
+        .unwrap_or_default()
+        .join(" ")
+        .split(['(', ')'])
+        .filter_map(|entry| {
+            let (code, priority) = entry.trim().split_once(' ')?;
+            Some((code.to_string(), priority.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Matches the language, voice name, or voice file (with or without its
+/// directory, so `mb/mb-en1` matches `mb-en1` and `!v/f3` matches `f3`).
+fn row_matches(row: &[String], wanted: &str) -> bool {
+    let [_priority, language, _gender, name, file, ..] = row else {
+        return false;
+    };
+    language == wanted
+        || name == wanted
+        || file == wanted
+        || file.rsplit('/').next() == Some(wanted)
+}
+
+/// Parses the `espeak --voices` table:
+///
+/// ```text
+/// Pty Language Age/Gender VoiceName          File          Other Languages
+///  5  en-us          M  english-us           en/en-us      (en 2)
+/// ```
+fn parse_voices(listing: &str) -> Vec<Voice> {
+    listing
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let columns: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+            let [_priority, language, gender, name, ..] = columns.as_slice() else {
+                return None;
+            };
+            let gender = match gender.trim_start_matches("--/") {
+                g if g.ends_with('M') => Some("male".to_string()),
+                g if g.ends_with('F') => Some("female".to_string()),
+                _ => None,
+            };
+            // Other Languages lists codes the voice also answers to
+            let mut languages = vec![language.clone()];
+            languages.extend(other_languages(&columns).into_iter().map(|(code, _)| code));
+            Some(Voice {
+                name: name.clone(),
+                languages,
+                gender,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An excerpt of `espeak-ng --voices` from eSpeak NG 1.51.
+    const VOICES: &str = "\
+Pty Language       Age/Gender VoiceName          File                 Other Languages
+ 5  af              --/M      Afrikaans          gmw/af
+ 5  cmn             --/M      Chinese_(Mandarin,_latin_as_English) sit/cmn              (zh-cmn 5)(zh 5)
+ 5  en-029          --/M      English_(Caribbean) gmw/en-029           (en 10)
+ 2  en-gb           --/M      English_(Great_Britain) gmw/en               (en 2)
+ 5  en-gb-x-rp      --/M      English_(Received_Pronunciation) gmw/en-GB-x-rp       (en-gb 4)(en 5)
+ 2  en-us           --/M      English_(America)  gmw/en-US            (en 3)
+ 5  nb              --/M      Norwegian_Bokmål   gmw/nb               (no 5)
+ 5  pt              --/M      Portuguese_(Portugal) roa/pt               (pt-pt 5)
+ 5  pt-br           --/M      Portuguese_(Brazil) roa/pt-BR            (pt 6)
+ 5  yue             --/M      Chinese_(Cantonese) sit/yue              (zh-yue 5)(zh 8)
+";
+
+    fn resolve(tag: &str) -> Option<String> {
+        resolve_in(&language_table(&parse_rows(VOICES)), &LanguageTag::parse(tag).unwrap())
+    }
+
+    #[test]
+    fn parse_rows_skips_the_header() {
+        let rows = parse_rows(VOICES);
+        assert_eq!(rows.len(), 10);
+        assert_eq!(rows[3][..5], ["2", "en-gb", "--/M", "English_(Great_Britain)", "gmw/en"]);
+    }
+
+    #[test]
+    fn parse_voices_reads_names_genders_and_other_languages() {
+        let voices = parse_voices(VOICES);
+        let english = voices.iter().find(|v| v.name == "English_(Great_Britain)").unwrap();
+        assert_eq!(english.languages, ["en-gb", "en"]);
+        assert_eq!(english.gender.as_deref(), Some("male"));
+        assert_eq!(voices[0].languages, ["af"]);
+    }
+
+    #[test]
+    fn other_languages_resolve_to_the_preferred_voice() {
+        assert_eq!(resolve("en").as_deref(), Some("en-gb"));
+        assert_eq!(resolve("en-AU").as_deref(), Some("en-gb"));
+        assert_eq!(resolve("en-US").as_deref(), Some("en-us"));
+        assert_eq!(resolve("zh").as_deref(), Some("cmn"));
+        assert_eq!(resolve("no").as_deref(), Some("nb"));
+    }
+
+    #[test]
+    fn a_voice_of_its_own_beats_an_alias() {
+        assert_eq!(resolve("pt").as_deref(), Some("pt"));
+        assert_eq!(resolve("pt-BR").as_deref(), Some("pt-br"));
+        assert_eq!(resolve("de"), None);
+    }
+}
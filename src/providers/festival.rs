@@ -0,0 +1,125 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::process::{Command, Stdio};
+use tokio::io::AsyncWriteExt;
+
+use super::{command_version, SynthesisRequest, TtsProvider, Voice};
+use crate::audio::AudioFormat;
+
+pub struct FestivalProvider;
+
+impl FestivalProvider {
+    pub fn new() -> Self {
+        FestivalProvider
+    }
+}
+
+#[async_trait]
+impl TtsProvider for FestivalProvider {
+    fn name(&self) -> &str {
+        "festival"
+    }
+
+    fn description(&self) -> &str {
+        "Festival TTS engine"
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new("festival").arg("--version").output().is_ok()
+    }
+
+    fn output_format(&self) -> AudioFormat {
+        AudioFormat::Wav
+    }
+
+    fn supported_languages(&self) -> Vec<String> {
+        vec!["en".to_string()]
+    }
+
+    fn engine_version(&self) -> Option<String> {
+        command_version("festival")
+    }
+
+    fn max_input_bytes(&self) -> Option<usize> {
+        Some(4000)
+    }
+
+    async fn list_voices(&self) -> Result<Vec<Voice>> {
+        let stdout = run_script("(print (voice.list))").await?;
+
+        // Output is a Scheme list such as `(kal_diphone cmu_us_slt_arctic_hts)`
+        let voices = stdout
+            .split(|c: char| c == '(' || c == ')' || c.is_whitespace())
+            .filter(|name| !name.is_empty())
+            .map(|name| Voice {
+                name: name.to_string(),
+                languages: vec!["en".to_string()],
+                gender: None,
+            })
+            .collect();
+
+        Ok(voices)
+    }
+
+    async fn synthesize(&self, request: &SynthesisRequest) -> Result<Vec<u8>> {
+        // A unique file per call keeps concurrent runs from clobbering each
+        // other's audio; it is removed when dropped
+        let temp_file = tempfile::Builder::new().prefix("tts_festival_").suffix(".wav").tempfile()?;
+
+        // Voices are selected by calling `(voice_<name>)`; accept the name
+        // with or without that prefix, as listed by `voice.list`.
+        let mut script = String::new();
+        if let Some(voice) = &request.voice {
+            let name = voice.trim_start_matches("voice_");
+            let installed = self.list_voices().await?;
+            if !installed.iter().any(|v| v.name == name) {
+                return Err(anyhow!(
+                    "Unknown festival voice '{}'. Run 'tts-cli voices --provider festival' to list voices",
+                    voice
+                ));
+            }
+            script.push_str(&format!("(voice_{})\n", name));
+        }
+        script.push_str(&format!(
+            "(utt.save.wave (utt.synth (Utterance Text \"{}\")) \"{}\" 'riff)\n",
+            scheme_escape(&request.text),
+            scheme_escape(&temp_file.path().to_string_lossy())
+        ));
+
+        run_script(&script).await?;
+
+        let audio_data = std::fs::read(temp_file.path())
+            .map_err(|e| anyhow!("festival did not produce audio: {}", e))?;
+
+        Ok(audio_data)
+    }
+}
+
+/// Runs Scheme commands through `festival --pipe`, returning stdout.
+async fn run_script(script: &str) -> Result<String> {
+    let mut child = tokio::process::Command::new("festival")
+        .kill_on_drop(true)
+        .arg("--pipe")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(script.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+
+    // Scheme errors are reported on either stream without a failing exit code
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() || stdout.contains("SIOD ERROR") || stderr.contains("SIOD ERROR") {
+        return Err(anyhow!("festival command failed: {}{}", stderr, stdout));
+    }
+
+    Ok(stdout)
+}
+
+fn scheme_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,122 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::process::Command;
+
+use super::{command_version, ProsodySupport, SynthesisRequest, TtsProvider, Voice};
+use crate::audio::AudioFormat;
+
+pub struct FliteProvider;
+
+impl FliteProvider {
+    pub fn new() -> Self {
+        FliteProvider
+    }
+}
+
+#[async_trait]
+impl TtsProvider for FliteProvider {
+    fn name(&self) -> &str {
+        "flite"
+    }
+
+    fn description(&self) -> &str {
+        "Flite (Festival Lite) TTS engine"
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new("flite").arg("--version").output().is_ok()
+    }
+
+    fn output_format(&self) -> AudioFormat {
+        AudioFormat::Wav
+    }
+
+    fn supported_languages(&self) -> Vec<String> {
+        // The voices compiled into flite are all English
+        vec!["en".to_string()]
+    }
+
+    fn prosody_support(&self) -> ProsodySupport {
+        ProsodySupport {
+            rate: true,
+            ..ProsodySupport::default()
+        }
+    }
+
+    fn engine_version(&self) -> Option<String> {
+        command_version("flite")
+    }
+
+    fn max_input_bytes(&self) -> Option<usize> {
+        Some(4000)
+    }
+
+    async fn list_voices(&self) -> Result<Vec<Voice>> {
+        Ok(installed_voices()
+            .await?
+            .into_iter()
+            .map(|name| Voice {
+                name,
+                languages: vec!["en".to_string()],
+                gender: None,
+            })
+            .collect())
+    }
+
+    async fn synthesize(&self, request: &SynthesisRequest) -> Result<Vec<u8>> {
+        let temp_file = tempfile::Builder::new().prefix("tts_flite_").suffix(".wav").tempfile()?;
+
+        let mut cmd = tokio::process::Command::new("flite");
+        cmd.kill_on_drop(true);
+
+        // `-voice` takes a built-in voice name or a path to a .flitevox file
+        if let Some(voice) = &request.voice {
+            if !voice.ends_with(".flitevox") && !installed_voices().await?.contains(voice) {
+                return Err(anyhow!(
+                    "Unknown flite voice '{}'. Run 'tts-cli voices --provider flite' to list voices",
+                    voice
+                ));
+            }
+            cmd.arg("-voice").arg(voice);
+        }
+
+        if request.rate != 1.0 {
+            // Stretching the duration slows speech down
+            cmd.arg("--setf")
+                .arg(format!("duration_stretch={:.3}", 1.0 / request.rate));
+        }
+
+        let output = cmd
+            .arg("-t")
+            .arg(&request.text)
+            .arg("-o")
+            .arg(temp_file.path())
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(anyhow!("flite command failed: {}", String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(std::fs::read(temp_file.path())?)
+    }
+}
+
+/// Voices compiled into flite, from `flite -lv`:
+///
+/// ```text
+/// Voices available: kal awb_time kal16 awb rms slt
+/// ```
+async fn installed_voices() -> Result<Vec<String>> {
+    let output = tokio::process::Command::new("flite")
+        .kill_on_drop(true)
+        .arg("-lv")
+        .output()
+        .await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let voices = stdout
+        .split_once(':')
+        .map(|(_, names)| names.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default();
+    Ok(voices)
+}
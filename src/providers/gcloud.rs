@@ -0,0 +1,232 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use serde_json::json;
+
+use super::gcloud_auth::Authenticator;
+use super::{api_error, ProsodySupport, SynthesisRequest, TtsProvider, Voice};
+use crate::audio::AudioFormat;
+use crate::config::ProviderConfig;
+use crate::language::{self, LanguageTag};
+
+/// Language codes Google Cloud Text-to-Speech has voices for. The first entry
+/// of each language is what a bare language code (`en`) resolves to.
+const LANGUAGES: &[&str] = &[
+    "af-ZA", "ar-XA", "bg-BG", "bn-IN", "ca-ES", "cmn-CN", "cmn-TW", "cs-CZ", "da-DK", "de-DE",
+    "el-GR", "en-US", "en-GB", "en-AU", "en-IN", "es-ES", "es-US", "eu-ES", "fi-FI", "fil-PH",
+    "fr-FR", "fr-CA", "gl-ES", "gu-IN", "he-IL", "hi-IN", "hu-HU", "id-ID", "is-IS", "it-IT",
+    "ja-JP", "kn-IN", "ko-KR", "lt-LT", "lv-LV", "ml-IN", "mr-IN", "ms-MY", "nb-NO", "nl-NL",
+    "nl-BE", "pa-IN", "pl-PL", "pt-BR", "pt-PT", "ro-RO", "ru-RU", "sk-SK", "sr-RS", "sv-SE",
+    "ta-IN", "te-IN", "th-TH", "tr-TR", "uk-UA", "vi-VN", "yue-HK",
+];
+
+/// Google files Chinese under the spoken language, Mandarin (`cmn`) or
+/// Cantonese (`yue`), and Norwegian under Bokmål (`nb`), whereas most callers
+/// write `zh` and `no`.
+fn google_language(tag: &LanguageTag) -> LanguageTag {
+    let (language, region) = match (tag.language.as_str(), tag.script.as_deref(), tag.region.as_deref()) {
+        ("zh", _, Some("HK" | "MO")) => ("yue", Some("HK")),
+        ("zh", Some("Hant"), _) | ("zh", _, Some("TW")) => ("cmn", Some("TW")),
+        ("zh", _, region) => ("cmn", region),
+        ("no", _, region) => ("nb", region),
+        _ => return tag.clone(),
+    };
+    LanguageTag {
+        language: language.to_string(),
+        script: None,
+        region: region.map(str::to_string),
+        variants: tag.variants.clone(),
+    }
+}
+
+const DEFAULT_ENDPOINT: &str = "https://texttospeech.googleapis.com";
+
+pub struct GcloudProvider {
+    endpoint: String,
+    auth: Authenticator,
+}
+
+impl GcloudProvider {
+    pub fn new(config: ProviderConfig) -> Self {
+        GcloudProvider {
+            endpoint: config.endpoint_or("gcloud", DEFAULT_ENDPOINT),
+            auth: Authenticator::new(config.api_key),
+        }
+    }
+}
+
+#[async_trait]
+impl TtsProvider for GcloudProvider {
+    fn name(&self) -> &str {
+        "gcloud"
+    }
+
+    fn description(&self) -> &str {
+        "Google Cloud Text-to-Speech API"
+    }
+
+    fn is_available(&self) -> bool {
+        self.auth.has_credentials()
+    }
+
+    fn output_format(&self) -> AudioFormat {
+        AudioFormat::Mp3
+    }
+
+    fn supported_languages(&self) -> Vec<String> {
+        LANGUAGES.iter().map(|l| l.to_string()).collect()
+    }
+
+    fn resolve_language(&self, tag: &LanguageTag) -> Option<String> {
+        language::best_match(&google_language(tag), &self.supported_languages())
+    }
+
+    fn supports_ssml(&self) -> bool {
+        true
+    }
+
+    fn prosody_support(&self) -> ProsodySupport {
+        ProsodySupport {
+            rate: true,
+            pitch: true,
+            volume: true,
+        }
+    }
+
+    fn engine_version(&self) -> Option<String> {
+        Some("v1".to_string())
+    }
+
+    fn max_input_bytes(&self) -> Option<usize> {
+        Some(5000)
+    }
+
+    async fn list_voices(&self) -> Result<Vec<Voice>> {
+        let auth = self.auth.auth().await?;
+        let response = auth
+            .apply(reqwest::Client::new().get(format!("{}/v1/voices", self.endpoint)))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            return Err(api_error("Google Cloud TTS API", status, &error_text));
+        }
+
+        let response_json: serde_json::Value = response.json().await?;
+        let voices = response_json["voices"]
+            .as_array()
+            .ok_or_else(|| anyhow!("No voices in response"))?
+            .iter()
+            .filter_map(|voice| {
+                Some(Voice {
+                    name: voice["name"].as_str()?.to_string(),
+                    languages: voice["languageCodes"]
+                        .as_array()?
+                        .iter()
+                        .filter_map(|l| l.as_str().map(str::to_string))
+                        .collect(),
+                    gender: voice["ssmlGender"].as_str().map(str::to_lowercase),
+                })
+            })
+            .collect();
+
+        Ok(voices)
+    }
+
+    async fn synthesize(&self, request: &SynthesisRequest) -> Result<Vec<u8>> {
+        // Without an explicit voice (CLI or config `voice_mapping`) Google
+        // picks a default voice for the language code.
+        let mut voice = json!({
+            "languageCode": request.language,
+            "ssmlGender": "NEUTRAL"
+        });
+        if let Some(name) = &request.voice {
+            voice["name"] = json!(name);
+        }
+
+        let input = if request.ssml {
+            json!({ "ssml": request.text })
+        } else {
+            json!({ "text": request.text })
+        };
+
+        let request_body = json!({
+            "input": input,
+            "voice": voice,
+            "audioConfig": {
+                "audioEncoding": "MP3",
+                "sampleRateHertz": 22050,
+                "speakingRate": request.rate,
+                "pitch": request.pitch,
+                "volumeGainDb": request.volume
+            }
+        });
+
+        let client = reqwest::Client::new();
+        let auth = self.auth.auth().await?;
+
+        let response = auth
+            .apply(client.post(format!("{}/v1/text:synthesize", self.endpoint)))
+            .header("Content-Type", "application/json")
+            .json(&request_body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            return Err(api_error("Google Cloud TTS API", status, &error_text));
+        }
+
+        let response_json: serde_json::Value = response.json().await?;
+        let audio_content = response_json["audioContent"]
+            .as_str()
+            .ok_or_else(|| anyhow!("No audioContent in response"))?;
+
+        let audio_bytes = base64::engine::general_purpose::STANDARD.decode(audio_content)?;
+        Ok(audio_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(tag: &str) -> Option<String> {
+        GcloudProvider::new(ProviderConfig::default()).resolve_language(&LanguageTag::parse(tag).unwrap())
+    }
+
+    #[test]
+    fn chinese_and_norwegian_resolve_through_aliases() {
+        assert_eq!(resolve("zh").as_deref(), Some("cmn-CN"));
+        assert_eq!(resolve("zh-CN").as_deref(), Some("cmn-CN"));
+        assert_eq!(resolve("zh-TW").as_deref(), Some("cmn-TW"));
+        assert_eq!(resolve("zh-Hant").as_deref(), Some("cmn-TW"));
+        assert_eq!(resolve("zh-HK").as_deref(), Some("yue-HK"));
+        assert_eq!(resolve("no").as_deref(), Some("nb-NO"));
+        assert_eq!(resolve("nb-NO").as_deref(), Some("nb-NO"));
+    }
+
+    #[test]
+    fn voice_filter_follows_the_same_aliases() {
+        let provider = GcloudProvider::new(ProviderConfig::default());
+        let voice = |language: &str| Voice {
+            name: format!("{language}-Wavenet-A"),
+            languages: vec![language.to_string()],
+            gender: None,
+        };
+        let finds = |tag: &str, voice: &Voice| {
+            let tag = LanguageTag::parse(tag).unwrap();
+            crate::providers::voice_languages(&provider, &tag).iter().any(|t| voice.speaks(t))
+        };
+        assert!(finds("zh", &voice("cmn-CN")));
+        assert!(finds("zh", &voice("cmn-TW")));
+        assert!(finds("zh-TW", &voice("cmn-TW")));
+        assert!(!finds("zh-TW", &voice("cmn-CN")));
+        assert!(finds("no", &voice("nb-NO")));
+        assert!(finds("en", &voice("en-GB")));
+        assert!(!finds("en-US", &voice("en-GB")));
+    }
+}
@@ -0,0 +1,272 @@
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+use super::api_error;
+use crate::cache::{self, CachedToken};
+
+const SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const TOKEN_LIFETIME_SECS: u64 = 3600;
+
+/// Tokens printed by the gcloud CLI come without an expiry; gcloud refreshes
+/// them itself, so they are only reused for a few minutes.
+const GCLOUD_CLI_TOKEN_SECS: u64 = 300;
+
+/// A credentials file as written by `gcloud auth application-default login`
+/// or downloaded from the console for a service account.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CredentialsFile {
+    ServiceAccount {
+        client_email: String,
+        private_key: String,
+        #[serde(default = "default_token_uri")]
+        token_uri: String,
+    },
+    AuthorizedUser {
+        client_id: String,
+        client_secret: String,
+        refresh_token: String,
+    },
+}
+
+fn default_token_uri() -> String {
+    DEFAULT_TOKEN_URI.to_string()
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+/// How requests to Google Cloud are authorized.
+pub enum Auth {
+    /// `?key=` query parameter, from the provider's `api_key` config.
+    ApiKey(String),
+    /// OAuth2 access token sent as `Authorization: Bearer`.
+    Bearer(String),
+}
+
+impl Auth {
+    pub fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self {
+            Auth::ApiKey(key) => request.query(&[("key", key)]),
+            Auth::Bearer(token) => request.bearer_auth(token),
+        }
+    }
+}
+
+/// Produces credentials for Google Cloud requests, in order of preference:
+/// an API key, a service-account or authorized-user credentials file, and
+/// finally `gcloud auth print-access-token`.
+///
+/// Access tokens are kept in memory and in the cache directory until shortly
+/// before they expire.
+pub struct Authenticator {
+    api_key: Option<String>,
+    token: Mutex<Option<CachedToken>>,
+}
+
+impl Authenticator {
+    pub fn new(api_key: Option<String>) -> Self {
+        Authenticator {
+            api_key: api_key.filter(|key| !key.is_empty()),
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Whether any source of credentials is present, without contacting Google.
+    pub fn has_credentials(&self) -> bool {
+        self.api_key.is_some()
+            || credentials_path().is_some()
+            || Command::new("gcloud").arg("--version").output().is_ok()
+    }
+
+    pub async fn auth(&self) -> Result<Auth> {
+        if let Some(key) = &self.api_key {
+            return Ok(Auth::ApiKey(key.clone()));
+        }
+
+        let mut token = self.token.lock().await;
+        if let Some(cached) = token.as_ref().filter(|t| t.is_fresh()) {
+            return Ok(Auth::Bearer(cached.access_token.clone()));
+        }
+
+        let fresh = fetch_token().await?;
+        let access_token = fresh.access_token.clone();
+        *token = Some(fresh);
+        Ok(Auth::Bearer(access_token))
+    }
+}
+
+/// `GOOGLE_APPLICATION_CREDENTIALS`, or the application default credentials
+/// written by `gcloud auth application-default login` when they exist.
+fn credentials_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("GOOGLE_APPLICATION_CREDENTIALS") {
+        return Some(PathBuf::from(path));
+    }
+
+    let default = dirs::config_dir()?
+        .join("gcloud")
+        .join("application_default_credentials.json");
+    default.exists().then_some(default)
+}
+
+async fn fetch_token() -> Result<CachedToken> {
+    let Some(path) = credentials_path() else {
+        return gcloud_cli_token().await;
+    };
+
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| anyhow!("Failed to read Google credentials {}: {}", path.display(), e))?;
+    let credentials: CredentialsFile = serde_json::from_str(&content).map_err(|e| {
+        anyhow!(
+            "Unsupported Google credentials file {}: {}. Expected a service account key or application default credentials",
+            path.display(),
+            e
+        )
+    })?;
+
+    // Tokens are cached per identity, so switching credentials files never
+    // reuses a token issued to another account.
+    let identity = match &credentials {
+        CredentialsFile::ServiceAccount { client_email, .. } => client_email.clone(),
+        CredentialsFile::AuthorizedUser {
+            client_id, refresh_token, ..
+        } => format!("{}:{}", client_id, refresh_token),
+    };
+    let cache_name = hex::encode(Sha256::digest(format!("{}|{}", identity, SCOPE)));
+    if let Ok(Some(token)) = cache::get_cached_token(&cache_name).await {
+        return Ok(token);
+    }
+
+    let token = match credentials {
+        CredentialsFile::ServiceAccount {
+            client_email,
+            private_key,
+            token_uri,
+        } => {
+            let assertion = signed_jwt(&client_email, &private_key, &token_uri)?;
+            request_token(
+                &token_uri,
+                &[
+                    ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                    ("assertion", &assertion),
+                ],
+            )
+            .await?
+        }
+        CredentialsFile::AuthorizedUser {
+            client_id,
+            client_secret,
+            refresh_token,
+        } => {
+            request_token(
+                DEFAULT_TOKEN_URI,
+                &[
+                    ("grant_type", "refresh_token"),
+                    ("client_id", &client_id),
+                    ("client_secret", &client_secret),
+                    ("refresh_token", &refresh_token),
+                ],
+            )
+            .await?
+        }
+    };
+
+    // A token that cannot be cached is still usable for this run
+    let _ = cache::cache_token(&cache_name, &token).await;
+    Ok(token)
+}
+
+async fn request_token(token_uri: &str, form: &[(&str, &str)]) -> Result<CachedToken> {
+    let response = reqwest::Client::new().post(token_uri).form(form).send().await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await?;
+        return Err(api_error("Google OAuth2 token request", status, &error_text));
+    }
+
+    let token: TokenResponse = response.json().await?;
+    Ok(CachedToken {
+        access_token: token.access_token,
+        expires_at: unix_now() + token.expires_in.unwrap_or(TOKEN_LIFETIME_SECS),
+    })
+}
+
+/// Self-signed RS256 JWT asserting the service account's identity, exchanged
+/// at `token_uri` for an access token.
+fn signed_jwt(client_email: &str, private_key: &str, token_uri: &str) -> Result<String> {
+    let encode = |bytes: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+
+    let now = unix_now();
+    let header = json!({ "alg": "RS256", "typ": "JWT" });
+    let claims = json!({
+        "iss": client_email,
+        "scope": SCOPE,
+        "aud": token_uri,
+        "iat": now,
+        "exp": now + TOKEN_LIFETIME_SECS,
+    });
+    let signing_input = format!(
+        "{}.{}",
+        encode(header.to_string().as_bytes()),
+        encode(claims.to_string().as_bytes())
+    );
+
+    let key_pair = RsaKeyPair::from_pkcs8(&pem_to_der(private_key)?)
+        .map_err(|e| anyhow!("Invalid service account private key: {}", e))?;
+    let mut signature = vec![0; key_pair.public().modulus_len()];
+    key_pair
+        .sign(&RSA_PKCS1_SHA256, &SystemRandom::new(), signing_input.as_bytes(), &mut signature)
+        .map_err(|_| anyhow!("Failed to sign service account JWT"))?;
+
+    Ok(format!("{}.{}", signing_input, encode(&signature)))
+}
+
+fn pem_to_der(pem: &str) -> Result<Vec<u8>> {
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(body)
+        .map_err(|e| anyhow!("Invalid service account private key: {}", e))
+}
+
+async fn gcloud_cli_token() -> Result<CachedToken> {
+    let output = tokio::process::Command::new("gcloud")
+        .kill_on_drop(true)
+        .args(["auth", "print-access-token"])
+        .output()
+        .await
+        .map_err(|e| anyhow!("No Google credentials found. Set an api_key for gcloud in the config, point GOOGLE_APPLICATION_CREDENTIALS at a service account key, or install Google Cloud SDK and run 'gcloud auth application-default login': {}", e))?;
+
+    if !output.status.success() {
+        return Err(anyhow!("Failed to get gcloud access token. Please run 'gcloud auth application-default login': {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(CachedToken {
+        access_token: String::from_utf8(output.stdout)?.trim().to_string(),
+        expires_at: unix_now() + GCLOUD_CLI_TOKEN_SECS,
+    })
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
@@ -0,0 +1,364 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::audio::{self, AudioFormat, AudioSegment};
+use crate::config::Config;
+use crate::language::{self, LanguageTag};
+use crate::ssml::{self, SsmlSegment};
+
+mod command;
+mod espeak;
+mod festival;
+mod flite;
+mod gcloud;
+mod gcloud_auth;
+mod openai;
+mod pico;
+mod piper;
+mod say;
+
+pub use command::CommandProvider;
+pub use espeak::EspeakProvider;
+pub use festival::FestivalProvider;
+pub use flite::FliteProvider;
+pub use gcloud::GcloudProvider;
+pub use openai::OpenAiProvider;
+pub use pico::PicoProvider;
+pub use piper::PiperProvider;
+pub use say::SayProvider;
+
+/// Everything a provider needs to produce audio for a single utterance.
+///
+/// The whole request is hashed into the cache key, so every field added here
+/// automatically invalidates audio synthesized without it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SynthesisRequest {
+    pub text: String,
+    pub language: String,
+    pub voice: Option<String>,
+    /// `text` is an SSML document rather than plain text.
+    pub ssml: bool,
+    /// Speaking rate multiplier; 1.0 is the engine's normal speed.
+    pub rate: f32,
+    /// Pitch shift in semitones.
+    pub pitch: f32,
+    /// Volume gain in decibels.
+    pub volume: f32,
+}
+
+/// Which of rate, pitch and volume a provider applies natively. The rest are
+/// applied to its output afterwards.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProsodySupport {
+    pub rate: bool,
+    pub pitch: bool,
+    pub volume: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Voice {
+    /// Value to pass as `--voice`
+    pub name: String,
+    /// BCP-47 style language tags, e.g. `en-US`
+    pub languages: Vec<String>,
+    pub gender: Option<String>,
+}
+
+impl Voice {
+    /// Whether the voice speaks `wanted`. Parts the tag leaves out match
+    /// anything, so `en` matches `en-US` and `sr-Latn` matches `sr-Latn-RS`.
+    pub fn speaks(&self, wanted: &LanguageTag) -> bool {
+        self.languages
+            .iter()
+            .filter_map(|language| LanguageTag::parse(language).ok())
+            .any(|have| {
+                have.language == wanted.language
+                    && (wanted.script.is_none() || have.script == wanted.script)
+                    && (wanted.region.is_none() || have.region == wanted.region)
+            })
+    }
+}
+
+/// Tags to look for among `provider`'s voices for `tag`: the tag itself and
+/// what the provider maps it to, so `zh` also finds Google's `cmn` voices.
+/// The mapped tag is only as specific as the requested one.
+pub fn voice_languages(provider: &dyn TtsProvider, tag: &LanguageTag) -> Vec<LanguageTag> {
+    let mut tags = vec![tag.clone()];
+    let resolved = provider.resolve_language(tag).and_then(|code| LanguageTag::parse(&code).ok());
+    if let Some(mut resolved) = resolved {
+        if tag.region.is_none() && tag.script.is_none() {
+            resolved.region = None;
+        }
+        if tag.script.is_none() {
+            resolved.script = None;
+        }
+        resolved.variants.clear();
+        tags.push(resolved);
+    }
+    tags
+}
+
+#[async_trait]
+pub trait TtsProvider: Send + Sync {
+    /// Short identifier used on the command line and in the config file.
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    /// Whether the engine (binary, credentials, ...) is usable on this machine.
+    fn is_available(&self) -> bool;
+
+    /// Container/codec of the bytes returned by `synthesize`.
+    fn output_format(&self) -> AudioFormat;
+
+    /// Language codes the provider can speak, in its own spelling.
+    fn supported_languages(&self) -> Vec<String>;
+
+    /// Maps a requested language onto the provider's own code, or `None` when
+    /// the provider cannot speak it.
+    fn resolve_language(&self, tag: &LanguageTag) -> Option<String> {
+        language::best_match(tag, &self.supported_languages())
+    }
+
+    /// Whether `synthesize` accepts SSML. Providers that do not get plain text,
+    /// with `<break>` elements rendered as inserted silence.
+    fn supports_ssml(&self) -> bool {
+        false
+    }
+
+    fn prosody_support(&self) -> ProsodySupport {
+        ProsodySupport::default()
+    }
+
+    /// Version of the underlying engine, part of the cache key so upgrading
+    /// the engine does not keep serving audio from the old one.
+    fn engine_version(&self) -> Option<String> {
+        None
+    }
+
+    /// Longest input, in bytes, accepted in a single `synthesize` call.
+    /// Longer text is split into chunks by the caller.
+    fn max_input_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    async fn list_voices(&self) -> Result<Vec<Voice>> {
+        Ok(Vec::new())
+    }
+
+    /// Transcribes the text into IPA phonemes instead of speaking it.
+    async fn phonemes(&self, _request: &SynthesisRequest) -> Result<String> {
+        Err(anyhow!("{} cannot transcribe text into phonemes", self.name()))
+    }
+
+    async fn synthesize(&self, request: &SynthesisRequest) -> Result<Vec<u8>>;
+}
+
+pub struct ProviderRegistry {
+    providers: Vec<Box<dyn TtsProvider>>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        ProviderRegistry {
+            providers: Vec::new(),
+        }
+    }
+
+    /// Registry populated with every provider that ships with tts-cli, plus
+    /// the command providers defined in `config`.
+    ///
+    /// Registration order is also the fallback order, so the offline engines
+    /// come before the network-backed ones. Providers that talk to a service
+    /// take their credentials from the matching `providers` entry of `config`.
+    pub fn with_builtin(config: &Config) -> Self {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Box::new(EspeakProvider::new()));
+        registry.register(Box::new(FestivalProvider::new()));
+        registry.register(Box::new(SayProvider::new()));
+        registry.register(Box::new(PicoProvider::new()));
+        registry.register(Box::new(FliteProvider::new()));
+        registry.register(Box::new(PiperProvider::new(
+            config.get_provider_config("piper").cloned().unwrap_or_default(),
+        )));
+        registry.register(Box::new(GcloudProvider::new(
+            config.get_provider_config("gcloud").cloned().unwrap_or_default(),
+        )));
+        registry.register(Box::new(OpenAiProvider::new(
+            config.get_provider_config("openai").cloned().unwrap_or_default(),
+        )));
+
+        // Command providers come last, sorted for a stable fallback order. One
+        // named like a built-in provider replaces it.
+        let mut commands: Vec<_> = config
+            .providers
+            .iter()
+            .filter_map(|(name, provider)| Some((name, provider.command.clone()?)))
+            .collect();
+        commands.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, command) in commands {
+            registry.register(Box::new(CommandProvider::new(name, command)));
+        }
+
+        registry
+    }
+
+    /// Adds a provider, replacing any previously registered one with the same name.
+    pub fn register(&mut self, provider: Box<dyn TtsProvider>) {
+        self.providers.retain(|p| p.name() != provider.name());
+        self.providers.push(provider);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn TtsProvider> {
+        self.providers
+            .iter()
+            .find(|p| p.name() == name)
+            .map(|p| p.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn TtsProvider> {
+        self.providers.iter().map(|p| p.as_ref())
+    }
+
+    pub fn list_providers(&self) {
+        println!("Available TTS providers:");
+        for provider in self.iter() {
+            let status = if provider.is_available() { "✓" } else { "✗" };
+            println!(
+                "  {} {} - {} [{}; {} languages]",
+                status,
+                provider.name(),
+                provider.description(),
+                provider.output_format().extension(),
+                provider.supported_languages().len()
+            );
+        }
+    }
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        ProviderRegistry::with_builtin(&Config::default())
+    }
+}
+
+/// Synthesizes with `provider`, mapping the language onto the provider's own
+/// code, translating SSML, and applying rate, pitch and volume for engines
+/// that cannot do so themselves.
+pub async fn synthesize(provider: &dyn TtsProvider, request: &SynthesisRequest) -> Result<Vec<u8>> {
+    let request = &with_provider_language(provider, request)?;
+
+    let audio_data = if !request.ssml || provider.supports_ssml() {
+        provider.synthesize(request).await?
+    } else {
+        synthesize_ssml_as_text(provider, request).await?
+    };
+
+    apply_missing_prosody(provider.prosody_support(), request, audio_data).await
+}
+
+/// IPA transcription of the request's text by `provider`.
+pub async fn phonemes(provider: &dyn TtsProvider, request: &SynthesisRequest) -> Result<String> {
+    provider.phonemes(&with_provider_language(provider, request)?).await
+}
+
+fn with_provider_language(provider: &dyn TtsProvider, request: &SynthesisRequest) -> Result<SynthesisRequest> {
+    let tag = LanguageTag::parse(&request.language)?;
+    let language = provider
+        .resolve_language(&tag)
+        .ok_or_else(|| anyhow!("{} does not support language {}", provider.name(), tag))?;
+    Ok(SynthesisRequest {
+        language,
+        ..request.clone()
+    })
+}
+
+async fn apply_missing_prosody(support: ProsodySupport, request: &SynthesisRequest, audio_data: Vec<u8>) -> Result<Vec<u8>> {
+    let rate = if support.rate { 1.0 } else { request.rate };
+    let pitch = if support.pitch { 0.0 } else { request.pitch };
+    let volume = if support.volume { 0.0 } else { request.volume };
+
+    let mut audio_data = audio_data;
+    if rate != 1.0 || pitch != 0.0 {
+        audio_data = audio::adjust_tempo_and_pitch(&audio_data, rate, pitch).await?;
+    }
+    if volume != 0.0 {
+        audio_data = audio::apply_gain(&audio_data, volume)?;
+    }
+
+    Ok(audio_data)
+}
+
+async fn synthesize_ssml_as_text(provider: &dyn TtsProvider, request: &SynthesisRequest) -> Result<Vec<u8>> {
+    let segments = ssml::segments(&request.text);
+    let mut clips = Vec::with_capacity(segments.len());
+    for segment in &segments {
+        if let SsmlSegment::Text(text) = segment {
+            let plain = SynthesisRequest {
+                text: text.clone(),
+                ssml: false,
+                ..request.clone()
+            };
+            clips.push(provider.synthesize(&plain).await?);
+        }
+    }
+
+    if clips.len() == 1 && !segments.iter().any(|s| matches!(s, SsmlSegment::Break(_))) {
+        return Ok(clips.remove(0));
+    }
+
+    let mut clips = clips.iter();
+    let parts: Vec<AudioSegment> = segments
+        .iter()
+        .filter_map(|segment| match segment {
+            SsmlSegment::Text(_) => clips.next().map(|clip| AudioSegment::Clip(clip)),
+            SsmlSegment::Break(pause) => Some(AudioSegment::Silence(*pause)),
+        })
+        .collect();
+    audio::join(&parts)
+}
+
+/// A failure that may go away when the request is repeated: rate limiting,
+/// a server error or a dropped connection.
+#[derive(Debug)]
+pub struct TransientError(String);
+
+impl fmt::Display for TransientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TransientError {}
+
+/// Error for an unsuccessful HTTP response from `service`. Rate limiting
+/// (429) and server errors (5xx) are marked as transient.
+fn api_error(service: &str, status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+    let message = format!("{} error ({}): {}", service, status, body.trim());
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        TransientError(message).into()
+    } else {
+        anyhow!(message)
+    }
+}
+
+/// Whether retrying might help, see `TransientError`.
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<TransientError>()
+            || cause
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|e| e.is_timeout() || e.is_connect())
+    })
+}
+
+/// First line of `<binary> --version`, used as the engine version of
+/// command-line providers.
+fn command_version(binary: &str) -> Option<String> {
+    let output = std::process::Command::new(binary).arg("--version").output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?.trim();
+    (!line.is_empty()).then(|| line.to_string())
+}
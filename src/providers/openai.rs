@@ -0,0 +1,234 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{api_error, ProsodySupport, SynthesisRequest, TtsProvider, Voice};
+use crate::audio::AudioFormat;
+use crate::config::ProviderConfig;
+
+const DEFAULT_ENDPOINT: &str = "https://api.openai.com";
+const DEFAULT_MODEL: &str = "tts-1";
+const DEFAULT_VOICE: &str = "alloy";
+
+/// Voices offered by OpenAI. Self-hosted servers often accept other names,
+/// which `--voice` passes through unchecked.
+const VOICES: &[&str] = &[
+    "alloy", "ash", "ballad", "coral", "echo", "fable", "nova", "onyx", "sage", "shimmer", "verse",
+];
+
+/// Every voice speaks every language; the language is inferred from the text.
+const LANGUAGES: &[&str] = &[
+    "af", "ar", "az", "be", "bg", "bs", "ca", "cs", "cy", "da", "de", "el", "en", "es", "et", "fa",
+    "fi", "fr", "gl", "he", "hi", "hr", "hu", "hy", "id", "is", "it", "ja", "kk", "kn", "ko", "lt",
+    "lv", "mi", "mk", "mr", "ms", "ne", "nl", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sr", "sv",
+    "sw", "ta", "th", "tl", "tr", "uk", "ur", "vi", "zh",
+];
+
+/// Any server implementing OpenAI's `/v1/audio/speech`: OpenAI itself or a
+/// self-hosted one such as LocalAI.
+pub struct OpenAiProvider {
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl OpenAiProvider {
+    pub fn new(config: ProviderConfig) -> Self {
+        OpenAiProvider {
+            endpoint: config.endpoint_or("openai", DEFAULT_ENDPOINT),
+            // Only a key from the config counts, never one that happens to be
+            // in the environment, so text is not sent to a paid API by accident
+            api_key: config.api_key.filter(|key| !key.is_empty()),
+            model: config.model.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl TtsProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn description(&self) -> &str {
+        "OpenAI-compatible speech API"
+    }
+
+    /// OpenAI needs a key; a self-hosted server at another endpoint may not.
+    fn is_available(&self) -> bool {
+        self.api_key.is_some() || self.endpoint != DEFAULT_ENDPOINT
+    }
+
+    fn output_format(&self) -> AudioFormat {
+        AudioFormat::Wav
+    }
+
+    fn supported_languages(&self) -> Vec<String> {
+        LANGUAGES.iter().map(|l| l.to_string()).collect()
+    }
+
+    fn prosody_support(&self) -> ProsodySupport {
+        ProsodySupport {
+            rate: true,
+            ..ProsodySupport::default()
+        }
+    }
+
+    /// The model decides what the audio sounds like, so it stands in for
+    /// the engine version in cache keys.
+    fn engine_version(&self) -> Option<String> {
+        Some(self.model.clone())
+    }
+
+    fn max_input_bytes(&self) -> Option<usize> {
+        // The API limits input to 4096 characters
+        Some(4096)
+    }
+
+    async fn list_voices(&self) -> Result<Vec<Voice>> {
+        Ok(VOICES
+            .iter()
+            .map(|name| Voice {
+                name: name.to_string(),
+                languages: self.supported_languages(),
+                gender: None,
+            })
+            .collect())
+    }
+
+    async fn synthesize(&self, request: &SynthesisRequest) -> Result<Vec<u8>> {
+        let request_body = json!({
+            "model": self.model,
+            "input": request.text,
+            "voice": request.voice.as_deref().unwrap_or(DEFAULT_VOICE),
+            "response_format": "wav",
+            "speed": request.rate,
+        });
+
+        let mut http_request = reqwest::Client::new()
+            .post(format!("{}/v1/audio/speech", self.endpoint))
+            .json(&request_body);
+        if let Some(key) = &self.api_key {
+            http_request = http_request.bearer_auth(key);
+        }
+
+        let response = http_request.send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            return Err(api_error("OpenAI speech API", status, &error_text));
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::is_retryable;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    /// Answers a single HTTP request with `status` and `body`, yielding the
+    /// request head and body it received.
+    async fn serve_once(status: &'static str, body: &'static [u8]) -> (String, JoinHandle<(String, Vec<u8>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buffer = [0u8; 4096];
+            let (head, body_start) = loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                received.extend_from_slice(&buffer[..read]);
+                if let Some(end) = received.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break (String::from_utf8_lossy(&received[..end]).to_string(), end + 4);
+                }
+            };
+            let content_length: usize = head
+                .to_ascii_lowercase()
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:")?.trim().parse().ok())
+                .unwrap_or(0);
+            while received.len() < body_start + content_length {
+                let read = socket.read(&mut buffer).await.unwrap();
+                received.extend_from_slice(&buffer[..read]);
+            }
+
+            let response = format!(
+                "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                status,
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            socket.write_all(body).await.unwrap();
+
+            (head, received[body_start..].to_vec())
+        });
+
+        (endpoint, server)
+    }
+
+    fn provider(endpoint: String) -> OpenAiProvider {
+        OpenAiProvider::new(ProviderConfig {
+            endpoint: Some(endpoint),
+            api_key: Some("sk-test".to_string()),
+            model: Some("tts-1-hd".to_string()),
+            ..ProviderConfig::default()
+        })
+    }
+
+    fn request() -> SynthesisRequest {
+        SynthesisRequest {
+            text: "Hello".to_string(),
+            language: "en".to_string(),
+            voice: Some("nova".to_string()),
+            ssml: false,
+            rate: 1.25,
+            pitch: 0.0,
+            volume: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn synthesize_posts_the_speech_request() {
+        let (endpoint, server) = serve_once("200 OK", b"RIFF audio").await;
+
+        let audio = provider(endpoint).synthesize(&request()).await.unwrap();
+        assert_eq!(audio, b"RIFF audio");
+
+        let (head, body) = server.await.unwrap();
+        assert!(head.starts_with("POST /v1/audio/speech HTTP/1.1"), "{}", head);
+        assert!(head.lines().any(|line| line.eq_ignore_ascii_case("authorization: Bearer sk-test")));
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "model": "tts-1-hd",
+                "input": "Hello",
+                "voice": "nova",
+                "response_format": "wav",
+                "speed": 1.25,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limits_and_server_errors_are_retryable() {
+        let cases = [
+            ("429 Too Many Requests", true),
+            ("503 Service Unavailable", true),
+            ("400 Bad Request", false),
+        ];
+        for (status, retryable) in cases {
+            let (endpoint, server) = serve_once(status, b"{\"error\": \"nope\"}").await;
+            let error = provider(endpoint).synthesize(&request()).await.unwrap_err();
+            assert_eq!(is_retryable(&error), retryable, "{}: {}", status, error);
+            server.await.unwrap();
+        }
+    }
+}
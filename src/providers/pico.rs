@@ -0,0 +1,88 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::process::Command;
+
+use super::{SynthesisRequest, TtsProvider, Voice};
+use crate::audio::AudioFormat;
+
+/// SVOX Pico has exactly one voice per language, named after it.
+const LANGUAGES: &[&str] = &["en-US", "en-GB", "de-DE", "es-ES", "fr-FR", "it-IT"];
+
+pub struct PicoProvider;
+
+impl PicoProvider {
+    pub fn new() -> Self {
+        PicoProvider
+    }
+}
+
+#[async_trait]
+impl TtsProvider for PicoProvider {
+    fn name(&self) -> &str {
+        "pico2wave"
+    }
+
+    fn description(&self) -> &str {
+        "SVOX Pico TTS engine"
+    }
+
+    fn is_available(&self) -> bool {
+        // pico2wave has no version flag; being able to start it is enough
+        Command::new("pico2wave").arg("--usage").output().is_ok()
+    }
+
+    fn output_format(&self) -> AudioFormat {
+        AudioFormat::Wav
+    }
+
+    fn supported_languages(&self) -> Vec<String> {
+        LANGUAGES.iter().map(|l| l.to_string()).collect()
+    }
+
+    fn max_input_bytes(&self) -> Option<usize> {
+        Some(4000)
+    }
+
+    async fn list_voices(&self) -> Result<Vec<Voice>> {
+        Ok(LANGUAGES
+            .iter()
+            .map(|language| Voice {
+                name: language.to_string(),
+                languages: vec![language.to_string()],
+                gender: Some("female".to_string()),
+            })
+            .collect())
+    }
+
+    async fn synthesize(&self, request: &SynthesisRequest) -> Result<Vec<u8>> {
+        // Voices are languages, so a voice simply selects another language
+        let language = request.voice.as_deref().unwrap_or(&request.language);
+        if !LANGUAGES.contains(&language) {
+            return Err(anyhow!(
+                "Unknown pico2wave voice '{}'. Available voices: {}",
+                language,
+                LANGUAGES.join(", ")
+            ));
+        }
+
+        // pico2wave only writes to files, and insists on a .wav extension
+        let temp_file = tempfile::Builder::new().prefix("tts_pico_").suffix(".wav").tempfile()?;
+
+        let output = tokio::process::Command::new("pico2wave")
+            .kill_on_drop(true)
+            .arg("-l")
+            .arg(language)
+            .arg("-w")
+            .arg(temp_file.path())
+            .arg("--")
+            .arg(&request.text)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(anyhow!("pico2wave command failed: {}", String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(std::fs::read(temp_file.path())?)
+    }
+}
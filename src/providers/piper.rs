@@ -0,0 +1,220 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tokio::io::AsyncWriteExt;
+
+use super::{command_version, ProsodySupport, SynthesisRequest, TtsProvider, Voice};
+use crate::audio::{self, AudioFormat, DecodedAudio};
+use crate::config::ProviderConfig;
+use crate::language::LanguageTag;
+
+/// Local neural TTS through the Piper binary. Voices are `.onnx` models,
+/// each with the `.onnx.json` config Piper ships alongside it.
+pub struct PiperProvider {
+    /// A model file, which is also the default voice for its language, or a
+    /// directory of models.
+    model: Option<PathBuf>,
+}
+
+impl PiperProvider {
+    pub fn new(config: ProviderConfig) -> Self {
+        PiperProvider {
+            model: config.model.map(PathBuf::from),
+        }
+    }
+
+    /// Directory searched for models: the configured one, the directory of
+    /// the configured model, or `~/.local/share/piper`.
+    fn model_dir(&self) -> Option<PathBuf> {
+        match &self.model {
+            Some(path) if path.is_dir() => Some(path.clone()),
+            // A bare file name such as `voice.onnx` has an empty parent
+            Some(path) => path
+                .parent()
+                .map(|dir| if dir.as_os_str().is_empty() { Path::new(".") } else { dir })
+                .map(Path::to_path_buf),
+            None => dirs::data_dir().map(|dir| dir.join("piper")),
+        }
+    }
+
+    fn models(&self) -> Vec<Model> {
+        let Some(entries) = self.model_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+            return Vec::new();
+        };
+
+        let mut models: Vec<Model> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "onnx"))
+            .filter_map(|path| Model::load(&path))
+            .collect();
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // The configured model wins over others speaking the same language.
+        // Models all come from its directory, so the file name identifies it
+        // however the path was spelled.
+        let configured_name = self.model.as_deref().and_then(Path::file_name);
+        if let Some(position) = models.iter().position(|m| m.path.file_name() == configured_name) {
+            let configured = models.remove(position);
+            models.insert(0, configured);
+        }
+
+        models
+    }
+
+    /// The model named by `--voice` (file stem or path), else the first one
+    /// speaking the request's language.
+    fn select_model(&self, request: &SynthesisRequest) -> Result<Model> {
+        match &request.voice {
+            Some(voice) if voice.ends_with(".onnx") => Model::load(Path::new(voice))
+                .ok_or_else(|| anyhow!("Piper model {} not found or missing its .onnx.json config", voice)),
+            Some(voice) => self.models().into_iter().find(|m| m.name == *voice).ok_or_else(|| {
+                anyhow!(
+                    "Unknown piper voice '{}'. Run 'tts-cli voices --provider piper' to list voices",
+                    voice
+                )
+            }),
+            None => self
+                .models()
+                .into_iter()
+                .find(|m| m.language.as_deref() == Some(request.language.as_str()))
+                .ok_or_else(|| anyhow!("No piper model installed for {}", request.language)),
+        }
+    }
+}
+
+#[async_trait]
+impl TtsProvider for PiperProvider {
+    fn name(&self) -> &str {
+        "piper"
+    }
+
+    fn description(&self) -> &str {
+        "Piper local neural TTS"
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new("piper").arg("--help").output().is_ok() && !self.models().is_empty()
+    }
+
+    fn output_format(&self) -> AudioFormat {
+        AudioFormat::Wav
+    }
+
+    fn supported_languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = self.models().into_iter().filter_map(|m| m.language).collect();
+        languages.sort();
+        languages.dedup();
+        languages
+    }
+
+    fn prosody_support(&self) -> ProsodySupport {
+        ProsodySupport {
+            rate: true,
+            ..ProsodySupport::default()
+        }
+    }
+
+    fn engine_version(&self) -> Option<String> {
+        command_version("piper")
+    }
+
+    fn max_input_bytes(&self) -> Option<usize> {
+        Some(4000)
+    }
+
+    async fn list_voices(&self) -> Result<Vec<Voice>> {
+        Ok(self
+            .models()
+            .into_iter()
+            .map(|model| Voice {
+                name: model.name,
+                languages: model.language.into_iter().collect(),
+                gender: None,
+            })
+            .collect())
+    }
+
+    async fn synthesize(&self, request: &SynthesisRequest) -> Result<Vec<u8>> {
+        let model = self.select_model(request)?;
+
+        // Piper's speed control is the inverse: a longer length scale is slower
+        let length_scale = model.length_scale / request.rate;
+
+        let mut child = tokio::process::Command::new("piper")
+            .kill_on_drop(true)
+            .arg("--model")
+            .arg(&model.path)
+            .arg("--output-raw")
+            .arg("--length_scale")
+            .arg(format!("{:.3}", length_scale))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to run piper: {}", e))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(request.text.as_bytes()).await?;
+            stdin.write_all(b"\n").await?;
+        }
+        let output = child.wait_with_output().await?;
+
+        if !output.status.success() || output.stdout.is_empty() {
+            return Err(anyhow!("piper command failed: {}", String::from_utf8_lossy(&output.stderr)));
+        }
+
+        // Raw output is 16-bit little-endian mono PCM at the model's rate
+        let samples = output
+            .stdout
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        audio::encode_wav(&DecodedAudio {
+            sample_rate: model.sample_rate,
+            channels: 1,
+            samples,
+        })
+    }
+}
+
+/// A voice model and the parts of its `.onnx.json` config tts-cli needs.
+struct Model {
+    /// File name without `.onnx`, e.g. `en_US-lessac-medium`
+    name: String,
+    path: PathBuf,
+    language: Option<String>,
+    sample_rate: u32,
+    length_scale: f32,
+}
+
+impl Model {
+    fn load(path: &Path) -> Option<Model> {
+        let name = path.file_stem()?.to_string_lossy().into_owned();
+        let mut config_path = path.as_os_str().to_owned();
+        config_path.push(".json");
+        let config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(config_path).ok()?).ok()?;
+
+        // Newer configs carry `language.code` (`en_US`). Older ones do not,
+        // but model files are named after it, and the espeak voice is a last
+        // resort
+        let language = [
+            config["language"]["code"].as_str(),
+            name.split('-').next(),
+            config["espeak"]["voice"].as_str(),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|code| LanguageTag::parse(code).ok())
+        .map(|tag| tag.to_string());
+
+        Some(Model {
+            path: path.to_path_buf(),
+            language,
+            sample_rate: config["audio"]["sample_rate"].as_u64().unwrap_or(22050) as u32,
+            length_scale: config["inference"]["length_scale"].as_f64().unwrap_or(1.0) as f32,
+            name,
+        })
+    }
+}
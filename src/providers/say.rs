@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::process::Command;
+
+use super::{ProsodySupport, SynthesisRequest, TtsProvider, Voice};
+use crate::audio::AudioFormat;
+
+pub struct SayProvider;
+
+impl SayProvider {
+    pub fn new() -> Self {
+        SayProvider
+    }
+}
+
+#[async_trait]
+impl TtsProvider for SayProvider {
+    fn name(&self) -> &str {
+        "say"
+    }
+
+    fn description(&self) -> &str {
+        "macOS built-in TTS"
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new("say").arg("--version").output().is_ok()
+    }
+
+    fn output_format(&self) -> AudioFormat {
+        AudioFormat::Wav
+    }
+
+    fn supported_languages(&self) -> Vec<String> {
+        let listing = Command::new("say").args(["-v", "?"]).output();
+        let mut languages: Vec<String> = listing
+            .map(|output| parse_voices(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|voice| voice.languages)
+            .collect();
+        languages.sort();
+        languages.dedup();
+        languages
+    }
+
+    fn prosody_support(&self) -> ProsodySupport {
+        ProsodySupport {
+            rate: true,
+            ..ProsodySupport::default()
+        }
+    }
+
+    async fn list_voices(&self) -> Result<Vec<Voice>> {
+        installed_voices().await
+    }
+
+    async fn synthesize(&self, request: &SynthesisRequest) -> Result<Vec<u8>> {
+        let temp_file = tempfile::Builder::new().prefix("tts_say_").suffix(".wav").tempfile()?;
+
+        // Ask for 16-bit WAV rather than the default AIFF, which cannot be
+        // decoded for in-process playback.
+        let mut cmd = tokio::process::Command::new("say");
+        cmd.kill_on_drop(true)
+            .arg("-o")
+            .arg(temp_file.path())
+            .arg("--file-format=WAVE")
+            .arg("--data-format=LEI16@22050");
+
+        // Without an explicit voice say uses the system voice whatever the
+        // language, so pick an installed voice for it instead
+        let voice = match &request.voice {
+            Some(voice) => Some(voice.clone()),
+            None => installed_voices()
+                .await?
+                .into_iter()
+                .find(|voice| voice.languages.contains(&request.language))
+                .map(|voice| voice.name),
+        };
+        if let Some(v) = &voice {
+            cmd.arg("-v").arg(v);
+        }
+
+        if request.rate != 1.0 {
+            // say's default rate is roughly 175 words per minute
+            cmd.arg("-r").arg((175.0 * request.rate).round().to_string());
+        }
+
+        cmd.arg(&request.text);
+
+        let output = cmd.output().await?;
+
+        if !output.status.success() {
+            return Err(anyhow!("say command failed: {}", String::from_utf8_lossy(&output.stderr)));
+        }
+
+        let audio_data = std::fs::read(temp_file.path())?;
+
+        Ok(audio_data)
+    }
+}
+
+async fn installed_voices() -> Result<Vec<Voice>> {
+    let output = tokio::process::Command::new("say")
+        .kill_on_drop(true)
+        .args(["-v", "?"])
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow!("say command failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(parse_voices(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_voices(listing: &str) -> Vec<Voice> {
+    // Lines look like `Alex                en_US    # Most people recognize me by my voice.`
+    // and voice names may contain spaces, so the locale is the last word
+    // before the `#`.
+    listing
+        .lines()
+        .filter_map(|line| {
+            let description = line.split('#').next()?.trim();
+            let (name, locale) = description.rsplit_once(char::is_whitespace)?;
+            Some(Voice {
+                name: name.trim().to_string(),
+                languages: vec![locale.replace('_', "-")],
+                gender: None,
+            })
+        })
+        .collect()
+}
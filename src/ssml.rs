@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+/// A piece of SSML as seen by engines that only understand plain text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SsmlSegment {
+    Text(String),
+    Break(Duration),
+}
+
+/// Input is treated as SSML when it starts with a `<speak>` root element.
+pub fn is_ssml(text: &str) -> bool {
+    text.trim_start().starts_with("<speak")
+}
+
+/// Flattens SSML into text and pauses. `<break>` elements become pauses,
+/// every other tag is dropped, and the predefined XML entities are decoded.
+pub fn segments(ssml: &str) -> Vec<SsmlSegment> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = ssml;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            // Unterminated tag: keep it as literal text
+            text.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        if let Some(pause) = break_duration(tag) {
+            push_text(&mut segments, &mut text);
+            segments.push(SsmlSegment::Break(pause));
+        } else {
+            // Keep words on either side of a dropped tag apart
+            text.push(' ');
+        }
+    }
+    text.push_str(rest);
+    push_text(&mut segments, &mut text);
+
+    segments
+}
+
+fn push_text(segments: &mut Vec<SsmlSegment>, text: &mut String) {
+    let normalized = decode_entities(&text.split_whitespace().collect::<Vec<_>>().join(" "));
+    if !normalized.is_empty() {
+        segments.push(SsmlSegment::Text(normalized));
+    }
+    text.clear();
+}
+
+fn break_duration(tag: &str) -> Option<Duration> {
+    let tag = tag.trim().trim_end_matches('/').trim();
+    let attributes = tag.strip_prefix("break")?;
+    if !attributes.is_empty() && !attributes.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    if let Some(time) = attribute(attributes, "time") {
+        return parse_time(&time);
+    }
+
+    // Strength values follow the defaults Google documents for <break>
+    let millis = match attribute(attributes, "strength").as_deref() {
+        Some("none") => 0,
+        Some("x-weak") => 100,
+        Some("weak") => 250,
+        Some("strong") => 750,
+        Some("x-strong") => 1000,
+        _ => 500,
+    };
+    Some(Duration::from_millis(millis))
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let start = attributes.find(&format!("{}=", name))? + name.len() + 1;
+    let value = &attributes[start..];
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    Some(value[..value.find(quote)?].to_string())
+}
+
+fn parse_time(time: &str) -> Option<Duration> {
+    let time = time.trim();
+    let secs = if let Some(millis) = time.strip_suffix("ms") {
+        millis.trim().parse::<f64>().ok()? / 1000.0
+    } else {
+        time.strip_suffix('s')?.trim().parse::<f64>().ok()?
+    };
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> SsmlSegment {
+        SsmlSegment::Text(s.to_string())
+    }
+
+    fn pause(millis: u64) -> SsmlSegment {
+        SsmlSegment::Break(Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn segments_split_text_at_breaks() {
+        let ssml = r#"<speak>Hello <break time="300ms"/> world<break strength="strong"/>!</speak>"#;
+        assert_eq!(segments(ssml), [text("Hello"), pause(300), text("world"), pause(750), text("!")]);
+    }
+
+    #[test]
+    fn segments_drop_other_tags_and_decode_entities() {
+        let ssml = "<speak><p>Fish &amp; chips</p><p>cost <emphasis>less</emphasis></p></speak>";
+        assert_eq!(segments(ssml), [text("Fish & chips cost less")]);
+    }
+
+    #[test]
+    fn segments_keep_an_unterminated_tag_as_text() {
+        assert_eq!(segments("<speak>a < b"), [text("a < b")]);
+    }
+
+    #[test]
+    fn break_duration_reads_time_and_strength() {
+        assert_eq!(break_duration("break time=\"1.5s\"/"), Some(Duration::from_millis(1500)));
+        assert_eq!(break_duration("break time='250ms'"), Some(Duration::from_millis(250)));
+        assert_eq!(break_duration("break strength=\"x-weak\" /"), Some(Duration::from_millis(100)));
+        assert_eq!(break_duration("break/"), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn break_duration_ignores_other_tags_and_bad_times() {
+        assert_eq!(break_duration("breakfast"), None);
+        assert_eq!(break_duration("p"), None);
+        assert_eq!(break_duration("break time=\"soon\""), None);
+        assert_eq!(break_duration("break time=\"-1s\""), None);
+    }
+}
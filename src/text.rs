@@ -0,0 +1,195 @@
+use anyhow::{anyhow, Context, Result};
+use std::io::{IsTerminal, Read};
+use std::path::Path;
+
+/// Resolves the text to synthesize from the positional argument, `--file`, or stdin.
+///
+/// A missing argument or `-` reads stdin, unless stdin is a terminal.
+pub fn read_input(text: Option<String>, file: Option<&Path>) -> Result<String> {
+    let text = if let Some(path) = file {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        decode_text(&bytes).with_context(|| format!("Failed to decode {}", path.display()))?
+    } else {
+        match text {
+            Some(text) if text != "-" => text,
+            _ => {
+                let mut stdin = std::io::stdin();
+                if stdin.is_terminal() {
+                    return Err(anyhow!("No text provided. Pass it as an argument, with --file, or on stdin"));
+                }
+                let mut bytes = Vec::new();
+                stdin.read_to_end(&mut bytes)?;
+                decode_text(&bytes).context("Failed to decode stdin")?
+            }
+        }
+    };
+
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow!("Input text is empty"));
+    }
+
+    Ok(text.to_string())
+}
+
+/// Decodes UTF-8 or UTF-16 input, using the byte order mark when present.
+pub fn decode_text(bytes: &[u8]) -> Result<String> {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => Ok(String::from_utf8(rest.to_vec())?),
+        [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8(bytes.to_vec())
+            .map_err(|_| anyhow!("Input is not valid UTF-8 (UTF-16 input needs a byte order mark)")),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Result<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(anyhow!("UTF-16 input has an odd number of bytes"));
+    }
+
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]))
+        .collect();
+
+    String::from_utf16(&units).map_err(|e| anyhow!("Invalid UTF-16 input: {}", e))
+}
+
+/// Splits text into chunks of at most `max_bytes` bytes for providers with an
+/// input limit. Sentence boundaries are preferred, then whitespace, and only
+/// then arbitrary character boundaries.
+pub fn split_into_chunks(text: &str, max_bytes: usize) -> Vec<String> {
+    if text.len() <= max_bytes {
+        return vec![text.to_string()];
+    }
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for sentence in split_sentences(text) {
+        for piece in split_oversized(sentence, max_bytes) {
+            if !current.is_empty() && current.len() + 1 + piece.len() > max_bytes {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(piece);
+        }
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let boundary = match c {
+            '\n' | '。' | '！' | '？' => true,
+            '.' | '!' | '?' | ';' => chars.peek().is_none_or(|(_, next)| next.is_whitespace()),
+            _ => false,
+        };
+
+        if boundary {
+            let end = i + c.len_utf8();
+            let sentence = text[start..end].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence);
+            }
+            start = end;
+        }
+    }
+
+    let sentence = text[start..].trim();
+    if !sentence.is_empty() {
+        sentences.push(sentence);
+    }
+
+    sentences
+}
+
+fn split_oversized(sentence: &str, max_bytes: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = sentence;
+
+    while rest.len() > max_bytes {
+        let mut cut = max_bytes;
+        while !rest.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        if let Some(space) = rest[..cut].rfind(char::is_whitespace).filter(|&i| i > 0) {
+            cut = space;
+        }
+        if cut == 0 {
+            cut = rest.chars().next().map_or(rest.len(), char::len_utf8);
+        }
+
+        pieces.push(rest[..cut].trim_end());
+        rest = rest[cut..].trim_start();
+    }
+
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_text_reads_utf8_with_or_without_bom() {
+        assert_eq!(decode_text("héllo".as_bytes()).unwrap(), "héllo");
+        assert_eq!(decode_text(b"\xEF\xBB\xBFhello").unwrap(), "hello");
+    }
+
+    #[test]
+    fn decode_text_reads_utf16_by_bom() {
+        assert_eq!(decode_text(b"\xFF\xFEh\0i\0").unwrap(), "hi");
+        assert_eq!(decode_text(b"\xFE\xFF\0h\0i").unwrap(), "hi");
+    }
+
+    #[test]
+    fn decode_text_rejects_invalid_input() {
+        assert!(decode_text(b"\xFF\xFEh\0i").is_err());
+        assert!(decode_text(b"\xC3\x28").is_err());
+    }
+
+    #[test]
+    fn split_into_chunks_leaves_short_text_alone() {
+        assert_eq!(split_into_chunks("Hello. World.", 100), ["Hello. World."]);
+    }
+
+    #[test]
+    fn split_into_chunks_packs_whole_sentences() {
+        let chunks = split_into_chunks("One two. Three four. Five six seven.", 20);
+        assert_eq!(chunks, ["One two. Three four.", "Five six seven."]);
+
+        // Decimal points are not sentence ends
+        assert_eq!(split_into_chunks("Pi is 3.14 or so. Yes.", 18), ["Pi is 3.14 or so.", "Yes."]);
+    }
+
+    #[test]
+    fn split_into_chunks_breaks_long_sentences_at_whitespace_then_characters() {
+        assert_eq!(split_into_chunks("alpha beta gamma delta", 11), ["alpha beta", "gamma delta"]);
+        assert_eq!(split_into_chunks("abcdefgh", 3), ["abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn split_into_chunks_never_splits_a_character() {
+        let chunks = split_into_chunks("ééééé", 3);
+        assert_eq!(chunks, ["é", "é", "é", "é", "é"]);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 3));
+    }
+}